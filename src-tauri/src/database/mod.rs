@@ -13,12 +13,37 @@ use std::sync::Arc;
 pub static SQLITE_CONNECTIONS: Lazy<Mutex<HashMap<String, rusqlite::Connection>>> = 
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// File watchers for SQLite connections, keyed by connection ID
+pub static SQLITE_WATCHERS: Lazy<Mutex<HashMap<String, notify::RecommendedWatcher>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Session extension objects recording changes, keyed by connection ID
+#[cfg(feature = "sqlite-session")]
+pub static SQLITE_SESSIONS: Lazy<Mutex<HashMap<String, sqlite::session::RecordingSession>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 pub static MONGO_CONNECTIONS: Lazy<Mutex<HashMap<String, ::mongodb::Client>>> = 
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// Running change stream watches, keyed by watch ID, with the connection each one belongs to
+pub static MONGO_WATCHES: Lazy<Mutex<HashMap<String, mongodb::watch::RunningWatch>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 pub static POSTGRES_CONNECTIONS: Lazy<Mutex<HashMap<String, Arc<tokio_postgres::Client>>>> = 
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// Connection strings of open PostgreSQL connections, for work that needs a session of its own
+pub static POSTGRES_CONNECTION_STRINGS: Lazy<Mutex<HashMap<String, String>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Notices received on each PostgreSQL connection since its last query started
+pub static POSTGRES_NOTICES: Lazy<Mutex<HashMap<String, Vec<models::PostgresNotice>>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Background tasks polling pg_stat_activity, keyed by connection
+pub static POSTGRES_MONITORS: Lazy<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>> = 
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 pub fn generate_connection_id() -> String {
     Uuid::new_v4().to_string()
 }
\ No newline at end of file
@@ -12,6 +12,22 @@ pub struct ConnectionConfig {
     pub options: Option<HashMap<String, String>>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct SqliteOptions {
+    // SQLCipher passphrase, or a hex encoded key when raw_key is set
+    pub key: Option<String>,
+    pub raw_key: Option<bool>,
+    pub cipher_compatibility: Option<u32>,
+    pub cipher_page_size: Option<u32>,
+    pub kdf_iter: Option<u32>,
+    pub cipher_hmac_algorithm: Option<String>,
+    pub cipher_kdf_algorithm: Option<String>,
+    // Paths of loadable extensions, e.g. mod_spatialite
+    pub extensions: Option<Vec<String>>,
+    // Optional built-in function groups: regexp, uuid, json, math
+    pub functions: Option<Vec<String>>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ConnectionResponse {
     pub connection_id: String,
@@ -26,6 +42,8 @@ pub struct QueryResult {
     pub affected_rows: Option<u64>,
     pub success: bool,
     pub error: Option<String>,
+    // Server notices raised while the query ran (PostgreSQL only)
+    pub notices: Option<Vec<PostgresNotice>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,4 +56,396 @@ pub struct DatabaseItem {
 #[derive(Debug, Serialize)]
 pub struct DatabaseStructure {
     pub items: Vec<DatabaseItem>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize)]
+pub struct PragmaInfo {
+    pub name: String,
+    pub value: serde_json::Value,
+    pub value_type: String,
+    pub persistent: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SqlitePragmas {
+    pub pragmas: Vec<PragmaInfo>,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub page_size: i64,
+    pub file_size: Option<u64>,
+    pub wal_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SqliteChangeEvent {
+    pub connection_id: String,
+    pub path: String,
+    pub data_version: i64,
+}
+
+#[cfg(feature = "sqlite-session")]
+#[derive(Debug, Serialize)]
+pub struct ChangesetEntry {
+    pub table: String,
+    pub operation: String,
+    pub indirect: bool,
+    pub columns: Vec<String>,
+    pub primary_key: Vec<usize>,
+    pub old_values: Vec<serde_json::Value>,
+    pub new_values: Vec<serde_json::Value>,
+    pub changed_columns: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub position: i16,
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub identity: Option<String>,
+    pub generated: Option<String>,
+    pub collation: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConstraintInfo {
+    pub name: String,
+    pub constraint_type: String,
+    pub columns: Vec<String>,
+    pub definition: String,
+    pub referenced_table: Option<String>,
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub definition: String,
+    pub method: String,
+    pub is_unique: bool,
+    pub is_primary: bool,
+    pub is_valid: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartitionInfo {
+    pub name: String,
+    pub bound: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableDescription {
+    pub schema: String,
+    pub name: String,
+    pub kind: String,
+    pub comment: Option<String>,
+    pub partition_key: Option<String>,
+    pub columns: Vec<ColumnInfo>,
+    pub constraints: Vec<ConstraintInfo>,
+    pub indexes: Vec<IndexInfo>,
+    pub partitions: Vec<PartitionInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DatabaseObject {
+    pub schema: Option<String>,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PlanNode {
+    pub node_type: String,
+    pub relation: Option<String>,
+    pub detail: Option<String>,
+    pub startup_cost: Option<f64>,
+    pub total_cost: Option<f64>,
+    pub plan_rows: Option<f64>,
+    pub actual_rows: Option<f64>,
+    pub actual_loops: Option<f64>,
+    pub actual_startup_time: Option<f64>,
+    pub actual_total_time: Option<f64>,
+    pub shared_hit_blocks: Option<i64>,
+    pub shared_read_blocks: Option<i64>,
+    pub temp_written_blocks: Option<i64>,
+    pub hot_spots: Vec<String>,
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryPlan {
+    pub root: PlanNode,
+    pub analyzed: bool,
+    pub planning_time: Option<f64>,
+    pub execution_time: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PostgresNotification {
+    pub connection_id: String,
+    pub channel: String,
+    pub payload: String,
+    pub process_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PostgresNotice {
+    pub severity: String,
+    pub code: String,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CopyOptions {
+    // csv (default), text or binary
+    pub format: Option<String>,
+    pub header: Option<bool>,
+    pub delimiter: Option<String>,
+    pub quote: Option<String>,
+    pub escape: Option<String>,
+    pub null: Option<String>,
+    pub encoding: Option<String>,
+    // Target columns for imports, all columns when empty
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CopyResult {
+    pub rows: Option<u64>,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CopyProgress {
+    pub connection_id: String,
+    pub path: String,
+    pub direction: String,
+    pub bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub pid: i32,
+    pub database: Option<String>,
+    pub user: Option<String>,
+    pub application: Option<String>,
+    pub client_address: Option<String>,
+    pub state: Option<String>,
+    pub wait_event_type: Option<String>,
+    pub wait_event: Option<String>,
+    pub query: Option<String>,
+    pub query_duration_ms: Option<f64>,
+    pub transaction_duration_ms: Option<f64>,
+    pub blocking_pids: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockNode {
+    pub pid: i32,
+    pub user: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+    pub duration_ms: Option<f64>,
+    pub blocked_by: Vec<i32>,
+    // The lock this backend is waiting for, if any
+    pub lock_type: Option<String>,
+    pub mode: Option<String>,
+    pub relation: Option<String>,
+    // Backends waiting on this one
+    pub children: Vec<LockNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivitySnapshot {
+    pub connection_id: String,
+    pub sessions: Vec<SessionInfo>,
+    pub locks: Vec<LockNode>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleInfo {
+    pub name: String,
+    pub superuser: bool,
+    pub inherit: bool,
+    pub create_role: bool,
+    pub create_db: bool,
+    pub can_login: bool,
+    pub replication: bool,
+    pub bypass_rls: bool,
+    pub connection_limit: i32,
+    pub valid_until: Option<String>,
+    pub member_of: Vec<String>,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrivilegeInfo {
+    pub grantee: String,
+    pub grantor: String,
+    pub privilege: String,
+    pub grantable: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectivePrivilege {
+    pub privilege: String,
+    pub granted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantRequest {
+    // grant or revoke
+    pub action: String,
+    pub privileges: Vec<String>,
+    pub object: DatabaseObject,
+    pub roles: Vec<String>,
+    // WITH GRANT OPTION, or GRANT OPTION FOR when revoking
+    pub with_grant_option: Option<bool>,
+    pub cascade: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableStats {
+    pub schema: String,
+    pub name: String,
+    // Sizes in bytes
+    pub total_size: i64,
+    pub table_size: i64,
+    pub indexes_size: i64,
+    pub toast_size: i64,
+    pub live_tuples: Option<i64>,
+    pub dead_tuples: Option<i64>,
+    pub last_vacuum: Option<String>,
+    pub last_autovacuum: Option<String>,
+    pub last_analyze: Option<String>,
+    pub last_autoanalyze: Option<String>,
+    pub seq_scans: Option<i64>,
+    pub seq_tuples_read: Option<i64>,
+    pub index_scans: Option<i64>,
+    pub index_tuples_fetched: Option<i64>,
+    // Estimated wasted bytes in the table heap, None until the table has been analyzed
+    pub estimated_bloat: Option<i64>,
+    pub bloat_ratio: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseSize {
+    pub name: String,
+    pub owner: String,
+    pub encoding: String,
+    pub size: Option<i64>,
+    pub connections: Option<i32>,
+    pub commits: Option<i64>,
+    pub rollbacks: Option<i64>,
+    pub cache_hit_ratio: Option<f64>,
+    pub deadlocks: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtensionInfo {
+    pub name: String,
+    pub installed_version: Option<String>,
+    pub default_version: Option<String>,
+    pub available_versions: Vec<String>,
+    pub update_available: bool,
+    pub schema: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionArgument {
+    pub name: Option<String>,
+    pub data_type: String,
+    // in, out, inout, variadic or table
+    pub mode: String,
+    pub has_default: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionInfo {
+    pub schema: String,
+    pub name: String,
+    pub signature: String,
+    pub identity_arguments: String,
+    pub result_type: Option<String>,
+    // function, procedure, aggregate or window
+    pub kind: String,
+    pub language: String,
+    pub volatility: String,
+    pub strict: bool,
+    pub security_definer: bool,
+    pub returns_set: bool,
+    pub comment: Option<String>,
+    pub source: String,
+    pub arguments: Vec<FunctionArgument>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PipelineStagePreview {
+    pub index: usize,
+    // Stage operator, e.g. $match or $group
+    pub stage: String,
+    pub columns: Vec<String>,
+    pub documents: Vec<serde_json::Value>,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldTypeCount {
+    pub bson_type: String,
+    pub count: u64,
+    // Share of the field's occurrences with this type
+    pub frequency: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldSchema {
+    // Dotted path, with [] marking array elements, e.g. items[].sku
+    pub path: String,
+    pub types: Vec<FieldTypeCount>,
+    pub occurrences: u64,
+    pub null_rate: f64,
+    // Share of sampled documents without the field
+    pub missing_rate: f64,
+    pub examples: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionSchema {
+    pub collection: String,
+    pub sampled_documents: u64,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MongoIndexInfo {
+    pub name: String,
+    pub keys: serde_json::Value,
+    // regular, text, 2dsphere, 2d, hashed or wildcard
+    pub kind: String,
+    pub unique: bool,
+    pub sparse: bool,
+    pub hidden: bool,
+    pub ttl_seconds: Option<u64>,
+    pub partial_filter: Option<serde_json::Value>,
+    // Operations served by the index since usage tracking started, from $indexStats
+    pub usage_count: Option<i64>,
+    pub usage_since: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MongoChangeEvent {
+    pub watch_id: String,
+    pub event: Option<serde_json::Value>,
+    // Pass back to watch as resume_after to continue after this event
+    pub resume_token: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
@@ -1,9 +1,25 @@
 use crate::database::{MONGO_CONNECTIONS, generate_connection_id};
-use crate::database::models::{ConnectionConfig, QueryResult};
-use mongodb::{Client, options::ClientOptions};
-use serde_json::{Value, json};
+use crate::database::models::{ConnectionConfig, PipelineStagePreview, QueryResult};
+use mongodb::{Client, Collection, Cursor};
+use mongodb::options::{
+    AggregateOptions, ClientOptions, Collation, FindOneAndDeleteOptions, FindOneAndReplaceOptions,
+    FindOneAndUpdateOptions, FindOptions, Hint, InsertManyOptions, ReplaceOptions, ReturnDocument,
+    UpdateModifications, UpdateOptions,
+};
+use mongodb::results::UpdateResult;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use std::time::{Duration, Instant};
 use futures::StreamExt;
-use mongodb::bson::{self, Document, doc};
+use mongodb::bson::{Bson, Document, doc};
+
+pub mod indexes;
+pub mod schema;
+pub mod shell;
+pub mod watch;
+
+// Finds without an explicit limit stop here so large collections don't flood the results grid
+const DEFAULT_FIND_LIMIT: i64 = 1000;
 
 pub async fn connect_mongodb(config: &ConnectionConfig) -> Result<String, String> {
     let connection_string = match &config.connection_string {
@@ -53,116 +69,61 @@ pub async fn connect_mongodb(config: &ConnectionConfig) -> Result<String, String
     Ok(connection_id)
 }
 
-pub async fn execute_query(connection_id: String, query: String) -> Result<QueryResult, String> {
-    // Get a clone of the client to avoid MutexGuard across await points
-    let client = {
-        let connections = MONGO_CONNECTIONS.lock().map_err(|e| e.to_string())?;
-        match connections.get(&connection_id) {
-            Some(client) => client.clone(),
-            None => return Err(format!("Connection with ID {} not found", connection_id)),
-        }
-    };
+pub fn close_connection(connection_id: &str) -> Result<(), String> {
+    // Change streams keep the client alive, so they're stopped along with the connection
+    watch::stop_watches(connection_id)?;
     
-    // Parse the query as JSON
-    let query_json: Value = match serde_json::from_str(&query) {
-        Ok(json) => json,
-        Err(e) => return Err(format!("Failed to parse MongoDB query as JSON: {}", e)),
-    };
-    
-    // Extract database and collection names
-    let db_name = match query_json.get("db").and_then(|v| v.as_str()) {
-        Some(name) => name,
-        None => return Err("MongoDB query must include a 'db' field".to_string()),
-    };
+    let mut connections = MONGO_CONNECTIONS.lock().map_err(|e| e.to_string())?;
     
-    let collection_name = match query_json.get("collection").and_then(|v| v.as_str()) {
-        Some(name) => name,
-        None => return Err("MongoDB query must include a 'collection' field".to_string()),
-    };
+    if connections.remove(connection_id).is_some() {
+        Ok(())
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
+pub async fn execute_query(connection_id: String, query: String, extended_json: Option<&str>) -> Result<QueryResult, String> {
+    let client = get_client(&connection_id)?;
     
-    // Get the database and collection
-    let db = client.database(db_name);
-    let collection = db.collection::<Document>(collection_name);
+    let query_json = parse_query(&client, &query)?;
+    let collection = get_collection(&client, &query_json)?;
+    let mode = json_mode(extended_json)?;
     
     // Determine the operation type
     match query_json.get("operation").and_then(|v| v.as_str()) {
         Some("find") => {
             // Extract the filter, if present
-            let filter = match query_json.get("filter") {
-                Some(filter_value) => {
-                    match bson::to_document(&filter_value) {
-                        Ok(doc) => doc,
-                        Err(e) => return Err(format!("Failed to parse filter: {}", e)),
-                    }
-                },
-                None => Document::new(),
-            };
+            let filter = parse_document(&query_json, "filter")?.unwrap_or_default();
+            
+            let options = find_options(&query_json)?;
             
             // Execute the find operation
-            let mut cursor = match collection.find(filter, None).await {
+            let cursor = match collection.find(filter, options).await {
                 Ok(cursor) => cursor,
                 Err(e) => return Err(format!("Failed to execute MongoDB find: {}", e)),
             };
             
-            // Collect the results
-            let mut rows = Vec::new();
-            let mut columns = Vec::new();
-            let mut column_set = std::collections::HashSet::new();
-
-            while let Some(result) = cursor.next().await {
-                match result {
-                    Ok(doc) => {
-                        // Extract column names from the document
-                        for key in doc.keys() {
-                            if !column_set.contains(key) {
-                                column_set.insert(key.clone());
-                                columns.push(key.clone());
-                            }
-                        }
-                        
-                        // Convert to JSON
-                        match bson::to_bson(&doc) {
-                            Ok(bson) => {
-                                match serde_json::to_value(&bson) {
-                                    Ok(json) => rows.push(json),
-                                    Err(e) => rows.push(json!({"error": format!("Failed to convert document to JSON: {}", e)})),
-                                }
-                            },
-                            Err(e) => rows.push(json!({"error": format!("Failed to convert document to BSON: {}", e)})),
-                        }
-                    },
-                    Err(e) => return Err(format!("Error while iterating MongoDB cursor: {}", e)),
-                }
-            }
+            Ok(documents_result(collect_documents(cursor).await?, mode))
+        },
+        Some("aggregate") => {
+            let pipeline = require_pipeline(&query_json)?;
+            let options = aggregate_options(&query_json)?;
             
-            let row_count = rows.len() as u64;
-            Ok(QueryResult {
-                columns,
-                rows,
-                affected_rows: Some(row_count),
-                success: true,
-                error: None,
-            })
+            let cursor = match collection.aggregate(pipeline, options).await {
+                Ok(cursor) => cursor,
+                Err(e) => return Err(format!("Failed to execute MongoDB aggregate: {}", e)),
+            };
+            
+            Ok(documents_result(collect_documents(cursor).await?, mode))
         },
         Some("insertOne") => {
             // Extract the document
-            let doc = match query_json.get("document") {
-                Some(doc_value) => {
-                    match bson::to_document(&doc_value) {
-                        Ok(doc) => doc,
-                        Err(e) => return Err(format!("Failed to parse document: {}", e)),
-                    }
-                },
-                None => return Err("MongoDB insertOne operation requires a 'document' field".to_string()),
-            };
+            let doc = require_document(&query_json, "document", "insertOne")?;
             
             // Execute the insertOne operation
             match collection.insert_one(doc, None).await {
                 Ok(result) => {
-                    let inserted_id = match serde_json::to_value(&result.inserted_id) {
-                        Ok(val) => val,
-                        Err(e) => json!({"error": format!("Failed to convert inserted ID: {}", e)}),
-                    };
+                    let inserted_id = bson_to_json(&result.inserted_id, mode);
                     
                     Ok(QueryResult {
                         columns: vec!["insertedId".to_string()],
@@ -170,26 +131,597 @@ pub async fn execute_query(connection_id: String, query: String) -> Result<Query
                         affected_rows: Some(1),
                         success: true,
                         error: None,
+                        notices: None,
                     })
                 },
                 Err(e) => Err(format!("Failed to execute MongoDB insertOne: {}", e)),
             }
         },
+        Some(op @ ("insertMany" | "updateOne" | "updateMany" | "replaceOne" | "deleteOne" | "deleteMany")) => {
+            let model = parse_write(op, &query_json)?;
+            let summary = write(&collection, op, model).await?;
+            Ok(write_result(op, summary, Vec::new(), mode))
+        },
+        Some("bulkWrite") => {
+            let operations = match query_json.get("operations").and_then(|v| v.as_array()) {
+                Some(operations) => operations,
+                None => return Err("MongoDB bulkWrite operation requires an 'operations' array".to_string()),
+            };
+            let ordered = query_json.get("ordered").and_then(|v| v.as_bool()).unwrap_or(true);
+            
+            // Every operation is parsed before any runs, so a malformed entry doesn't leave
+            // the collection half written
+            let mut models = Vec::new();
+            for (i, operation) in operations.iter().enumerate() {
+                let (op, spec) = match operation.as_object().filter(|o| o.len() == 1).and_then(|o| o.iter().next()) {
+                    Some((op, spec)) => (op.as_str(), spec),
+                    None => return Err(format!("Bulk write operation {} must be an object with a single operation", i)),
+                };
+                if !matches!(op, "insertOne" | "updateOne" | "updateMany" | "replaceOne" | "deleteOne" | "deleteMany") {
+                    return Err(format!("Unsupported bulk write operation: {}", op));
+                }
+                let model = parse_write(op, spec).map_err(|e| format!("Operation {} ({}): {}", i, op, e))?;
+                models.push((op, model));
+            }
+            
+            // Operations run one at a time; an ordered bulk write stops at the first failure,
+            // an unordered one carries on and reports every failure
+            let mut summary = WriteSummary::default();
+            let mut errors = Vec::new();
+            for (i, (op, model)) in models.into_iter().enumerate() {
+                match write(&collection, op, model).await {
+                    Ok(result) => summary.add(result),
+                    Err(e) => {
+                        errors.push(format!("Operation {} ({}): {}", i, op, e));
+                        if ordered {
+                            break;
+                        }
+                    },
+                }
+            }
+            
+            Ok(write_result("bulkWrite", summary, errors, mode))
+        },
+        Some(op @ ("findOneAndUpdate" | "findOneAndReplace" | "findOneAndDelete")) => {
+            let filter = parse_document(&query_json, "filter")?.unwrap_or_default();
+            let projection = parse_document(&query_json, "projection")?;
+            let sort = parse_document(&query_json, "sort")?;
+            let upsert = query_json.get("upsert").and_then(|v| v.as_bool());
+            
+            // Like mongosh, the document before the change is returned unless asked otherwise
+            let return_document = match query_json.get("returnDocument").and_then(|v| v.as_str()) {
+                Some("after") => ReturnDocument::After,
+                Some("before") | None => ReturnDocument::Before,
+                Some(other) => return Err(format!("Invalid returnDocument: {}", other)),
+            };
+            
+            let result = match op {
+                "findOneAndUpdate" => {
+                    let options = FindOneAndUpdateOptions::builder()
+                        .projection(projection)
+                        .sort(sort)
+                        .upsert(upsert)
+                        .array_filters(parse_documents(&query_json, "arrayFilters")?)
+                        .return_document(return_document)
+                        .build();
+                    collection.find_one_and_update(filter, parse_update(&query_json, op)?, options).await
+                },
+                "findOneAndReplace" => {
+                    let options = FindOneAndReplaceOptions::builder()
+                        .projection(projection)
+                        .sort(sort)
+                        .upsert(upsert)
+                        .return_document(return_document)
+                        .build();
+                    collection.find_one_and_replace(filter, require_document(&query_json, "replacement", op)?, options).await
+                },
+                _ => {
+                    let options = FindOneAndDeleteOptions::builder()
+                        .projection(projection)
+                        .sort(sort)
+                        .build();
+                    collection.find_one_and_delete(filter, options).await
+                },
+            };
+            
+            match result {
+                Ok(document) => {
+                    let mut result = documents_result(document.into_iter().collect(), mode);
+                    // The matched document is the result, but only one document was written
+                    result.affected_rows = Some(result.rows.len() as u64);
+                    Ok(result)
+                },
+                Err(e) => Err(format!("Failed to execute MongoDB {}: {}", op, e)),
+            }
+        },
+        Some("countDocuments") => {
+            let filter = parse_document(&query_json, "filter")?.unwrap_or_default();
+            match collection.count_documents(filter, None).await {
+                Ok(count) => Ok(count_result(count)),
+                Err(e) => Err(format!("Failed to execute MongoDB countDocuments: {}", e)),
+            }
+        },
+        Some("estimatedDocumentCount") => {
+            // Reads the collection metadata instead of scanning, so no filter applies
+            match collection.estimated_document_count(None).await {
+                Ok(count) => Ok(count_result(count)),
+                Err(e) => Err(format!("Failed to execute MongoDB estimatedDocumentCount: {}", e)),
+            }
+        },
+        Some("distinct") => {
+            let field = match query_json.get("field").and_then(|v| v.as_str()) {
+                Some(field) => field,
+                None => return Err("MongoDB distinct operation requires a 'field' field".to_string()),
+            };
+            let filter = parse_document(&query_json, "filter")?;
+            
+            match collection.distinct(field, filter, None).await {
+                Ok(values) => {
+                    let rows: Vec<Value> = values.iter().map(|v| json!({"value": bson_to_json(v, mode)})).collect();
+                    let row_count = rows.len() as u64;
+                    Ok(QueryResult {
+                        columns: vec!["value".to_string()],
+                        rows,
+                        affected_rows: Some(row_count),
+                        success: true,
+                        error: None,
+                        notices: None,
+                    })
+                },
+                Err(e) => Err(format!("Failed to execute MongoDB distinct: {}", e)),
+            }
+        },
         Some(op) => Err(format!("Unsupported MongoDB operation: {}", op)),
         None => Err("MongoDB query must include an 'operation' field".to_string()),
     }
 }
 
-pub async fn list_databases(connection_id: &str) -> Result<Vec<String>, String> {
-    // Get a clone of the client to avoid MutexGuard across await points
-    let client = {
-        let connections = MONGO_CONNECTIONS.lock().map_err(|e| e.to_string())?;
-        match connections.get(connection_id) {
-            Some(client) => client.clone(),
-            None => return Err(format!("Connection with ID {} not found", connection_id)),
+pub async fn preview_aggregate(
+    connection_id: String,
+    query: String,
+    sample_size: i64,
+    extended_json: Option<&str>,
+) -> Result<Vec<PipelineStagePreview>, String> {
+    if sample_size < 1 {
+        return Err("Sample size must be at least 1".to_string());
+    }
+    
+    let client = get_client(&connection_id)?;
+    
+    let query_json = parse_query(&client, &query)?;
+    let collection = get_collection(&client, &query_json)?;
+    let mode = json_mode(extended_json)?;
+    let pipeline = require_pipeline(&query_json)?;
+    
+    // Each preview runs the pipeline up to and including one stage, capped to a sample
+    let mut previews = Vec::new();
+    for (index, stage) in pipeline.iter().enumerate() {
+        let stage_name = stage.keys().next().cloned().unwrap_or_default();
+        let mut preview = PipelineStagePreview {
+            index,
+            stage: stage_name.clone(),
+            columns: Vec::new(),
+            documents: Vec::new(),
+            elapsed_ms: 0,
+            error: None,
+        };
+        
+        // Stages that write to other collections are never run from a preview
+        if stage_name == "$out" || stage_name == "$merge" {
+            preview.error = Some(format!("{} stages are not run in previews", stage_name));
+            previews.push(preview);
+            break;
+        }
+        
+        let mut partial = pipeline[..=index].to_vec();
+        partial.push(doc! {"$limit": sample_size});
+        
+        let started = Instant::now();
+        let result = match collection.aggregate(partial, aggregate_options(&query_json)?).await {
+            Ok(cursor) => collect_documents(cursor).await,
+            Err(e) => Err(format!("Failed to execute MongoDB aggregate: {}", e)),
+        };
+        preview.elapsed_ms = started.elapsed().as_millis() as u64;
+        
+        // Later stages can't run once a stage fails
+        match result {
+            Ok(documents) => {
+                let result = documents_result(documents, mode);
+                preview.columns = result.columns;
+                preview.documents = result.rows;
+                previews.push(preview);
+            },
+            Err(e) => {
+                preview.error = Some(e);
+                previews.push(preview);
+                break;
+            },
+        }
+    }
+    
+    Ok(previews)
+}
+
+// Output format for BSON values in results
+#[derive(Clone, Copy)]
+enum JsonMode {
+    Relaxed,
+    Canonical,
+}
+
+// Counts collected from write operations, summed across the operations of a bulk write
+#[derive(Default)]
+struct WriteSummary {
+    inserted_ids: Vec<Bson>,
+    matched: u64,
+    modified: u64,
+    deleted: u64,
+    upserted_ids: Vec<Bson>,
+}
+
+impl WriteSummary {
+    fn add(&mut self, other: WriteSummary) {
+        self.inserted_ids.extend(other.inserted_ids);
+        self.matched += other.matched;
+        self.modified += other.modified;
+        self.deleted += other.deleted;
+        self.upserted_ids.extend(other.upserted_ids);
+    }
+    
+    fn from_update(result: UpdateResult) -> WriteSummary {
+        WriteSummary {
+            matched: result.matched_count,
+            modified: result.modified_count,
+            upserted_ids: result.upserted_id.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+// A write operation parsed from its spec, ready to run
+enum WriteModel {
+    InsertOne(Document),
+    InsertMany(Vec<Document>, InsertManyOptions),
+    Update { filter: Document, update: UpdateModifications, options: UpdateOptions, many: bool },
+    Replace { filter: Document, replacement: Document, options: ReplaceOptions },
+    Delete { filter: Document, many: bool },
+}
+
+// Parses a single write operation described by spec, which holds the same fields for
+// standalone operations and for the entries of a bulk write
+fn parse_write(operation: &str, spec: &Value) -> Result<WriteModel, String> {
+    let filter = parse_document(spec, "filter")?.unwrap_or_default();
+    let upsert = spec.get("upsert").and_then(|v| v.as_bool());
+    
+    match operation {
+        "insertOne" => Ok(WriteModel::InsertOne(require_document(spec, "document", operation)?)),
+        "insertMany" => {
+            let docs = match parse_documents(spec, "documents")? {
+                Some(docs) => docs,
+                None => return Err("MongoDB insertMany operation requires a 'documents' array".to_string()),
+            };
+            let options = InsertManyOptions::builder()
+                .ordered(spec.get("ordered").and_then(|v| v.as_bool()))
+                .build();
+            Ok(WriteModel::InsertMany(docs, options))
+        },
+        "updateOne" | "updateMany" => Ok(WriteModel::Update {
+            filter,
+            update: parse_update(spec, operation)?,
+            options: UpdateOptions::builder()
+                .upsert(upsert)
+                .array_filters(parse_documents(spec, "arrayFilters")?)
+                .build(),
+            many: operation == "updateMany",
+        }),
+        "replaceOne" => Ok(WriteModel::Replace {
+            filter,
+            replacement: require_document(spec, "replacement", operation)?,
+            options: ReplaceOptions::builder().upsert(upsert).build(),
+        }),
+        "deleteOne" | "deleteMany" => Ok(WriteModel::Delete { filter, many: operation == "deleteMany" }),
+        op => Err(format!("Unsupported MongoDB operation: {}", op)),
+    }
+}
+
+async fn write(collection: &Collection<Document>, operation: &str, model: WriteModel) -> Result<WriteSummary, String> {
+    let result = match model {
+        WriteModel::InsertOne(doc) => {
+            collection.insert_one(doc, None).await.map(|result| WriteSummary {
+                inserted_ids: vec![result.inserted_id],
+                ..Default::default()
+            })
+        },
+        WriteModel::InsertMany(docs, options) => {
+            collection.insert_many(docs, options).await.map(|result| {
+                // Inserted IDs are keyed by the document's position in the input
+                let mut ids: Vec<(usize, Bson)> = result.inserted_ids.into_iter().collect();
+                ids.sort_by_key(|(i, _)| *i);
+                WriteSummary {
+                    inserted_ids: ids.into_iter().map(|(_, id)| id).collect(),
+                    ..Default::default()
+                }
+            })
+        },
+        WriteModel::Update { filter, update, options, many } => {
+            let result = if many {
+                collection.update_many(filter, update, options).await
+            } else {
+                collection.update_one(filter, update, options).await
+            };
+            result.map(WriteSummary::from_update)
+        },
+        WriteModel::Replace { filter, replacement, options } => {
+            collection.replace_one(filter, replacement, options).await.map(WriteSummary::from_update)
+        },
+        WriteModel::Delete { filter, many } => {
+            let result = if many {
+                collection.delete_many(filter, None).await
+            } else {
+                collection.delete_one(filter, None).await
+            };
+            result.map(|result| WriteSummary {
+                deleted: result.deleted_count,
+                ..Default::default()
+            })
+        },
+    };
+    
+    result.map_err(|e| format!("Failed to execute MongoDB {}: {}", operation, e))
+}
+
+fn write_result(operation: &str, summary: WriteSummary, errors: Vec<String>, mode: JsonMode) -> QueryResult {
+    let mut row = Map::new();
+    let is_bulk = operation == "bulkWrite";
+    
+    if is_bulk || operation == "insertMany" {
+        row.insert("insertedCount".to_string(), json!(summary.inserted_ids.len()));
+        row.insert("insertedIds".to_string(), json!(summary.inserted_ids.iter().map(|id| bson_to_json(id, mode)).collect::<Vec<_>>()));
+    }
+    if is_bulk || operation.starts_with("update") || operation == "replaceOne" {
+        row.insert("matchedCount".to_string(), json!(summary.matched));
+        row.insert("modifiedCount".to_string(), json!(summary.modified));
+        row.insert("upsertedCount".to_string(), json!(summary.upserted_ids.len()));
+        if is_bulk {
+            row.insert("upsertedIds".to_string(), json!(summary.upserted_ids.iter().map(|id| bson_to_json(id, mode)).collect::<Vec<_>>()));
+        } else {
+            row.insert("upsertedId".to_string(), summary.upserted_ids.first().map(|id| bson_to_json(id, mode)).unwrap_or(Value::Null));
         }
+    }
+    if is_bulk || operation.starts_with("delete") {
+        row.insert("deletedCount".to_string(), json!(summary.deleted));
+    }
+    
+    let affected = summary.inserted_ids.len() as u64 + summary.modified + summary.upserted_ids.len() as u64 + summary.deleted;
+    QueryResult {
+        columns: row.keys().cloned().collect(),
+        rows: vec![Value::Object(row)],
+        affected_rows: Some(affected),
+        success: errors.is_empty(),
+        error: if errors.is_empty() { None } else { Some(errors.join("\n")) },
+        notices: None,
+    }
+}
+
+fn count_result(count: u64) -> QueryResult {
+    QueryResult {
+        columns: vec!["count".to_string()],
+        rows: vec![json!({"count": count})],
+        affected_rows: Some(1),
+        success: true,
+        error: None,
+        notices: None,
+    }
+}
+
+fn documents_result(documents: Vec<Document>, mode: JsonMode) -> QueryResult {
+    let mut rows = Vec::new();
+    let mut columns = Vec::new();
+    let mut column_set = std::collections::HashSet::new();
+    
+    for doc in documents {
+        // Extract column names from the document
+        for key in doc.keys() {
+            if !column_set.contains(key) {
+                column_set.insert(key.clone());
+                columns.push(key.clone());
+            }
+        }
+        
+        // Convert to JSON
+        rows.push(bson_to_json(&Bson::Document(doc), mode));
+    }
+    
+    let row_count = rows.len() as u64;
+    QueryResult {
+        columns,
+        rows,
+        affected_rows: Some(row_count),
+        success: true,
+        error: None,
+        notices: None,
+    }
+}
+
+async fn collect_documents(mut cursor: Cursor<Document>) -> Result<Vec<Document>, String> {
+    let mut documents = Vec::new();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(doc) => documents.push(doc),
+            Err(e) => return Err(format!("Error while iterating MongoDB cursor: {}", e)),
+        }
+    }
+    Ok(documents)
+}
+
+// Relaxed Extended JSON keeps numbers and dates readable, canonical mode preserves every BSON type
+fn bson_to_json(value: &Bson, mode: JsonMode) -> Value {
+    match mode {
+        JsonMode::Relaxed => value.clone().into_relaxed_extjson(),
+        JsonMode::Canonical => value.clone().into_canonical_extjson(),
+    }
+}
+
+// The mode is passed alongside the query so it applies to JSON and shell syntax alike
+fn json_mode(mode: Option<&str>) -> Result<JsonMode, String> {
+    match mode {
+        Some("relaxed") | None => Ok(JsonMode::Relaxed),
+        Some("canonical") => Ok(JsonMode::Canonical),
+        Some(other) => Err(format!("Invalid extendedJson mode: {}", other)),
+    }
+}
+
+// Input values are read as Extended JSON, so {"$oid": "..."} becomes an ObjectId and
+// {"$date": "..."} a date instead of plain subdocuments
+fn json_to_document(value: &Value, field: &str) -> Result<Document, String> {
+    match Bson::try_from(value.clone()) {
+        Ok(Bson::Document(doc)) => Ok(doc),
+        Ok(_) => Err(format!("Failed to parse {}: expected a document", field)),
+        Err(e) => Err(format!("Failed to parse {}: {}", field, e)),
+    }
+}
+
+// Reads an optional document field such as a filter or projection
+fn parse_document(spec: &Value, field: &str) -> Result<Option<Document>, String> {
+    match spec.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => json_to_document(value, field).map(Some),
+    }
+}
+
+fn require_document(spec: &Value, field: &str, operation: &str) -> Result<Document, String> {
+    match parse_document(spec, field)? {
+        Some(doc) => Ok(doc),
+        None => Err(format!("MongoDB {} operation requires a '{}' field", operation, field)),
+    }
+}
+
+// Reads an optional array of documents such as arrayFilters or the documents to insert
+fn parse_documents(spec: &Value, field: &str) -> Result<Option<Vec<Document>>, String> {
+    match spec.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(values)) => values.iter()
+            .map(|value| json_to_document(value, field))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+        Some(_) => Err(format!("'{}' must be an array of documents", field)),
+    }
+}
+
+// Updates are either a document of update operators or an aggregation pipeline
+fn parse_update(spec: &Value, operation: &str) -> Result<UpdateModifications, String> {
+    match spec.get("update") {
+        Some(Value::Array(_)) => Ok(UpdateModifications::Pipeline(parse_documents(spec, "update")?.unwrap_or_default())),
+        _ => Ok(UpdateModifications::Document(require_document(spec, "update", operation)?)),
+    }
+}
+
+fn require_pipeline(spec: &Value) -> Result<Vec<Document>, String> {
+    match parse_documents(spec, "pipeline")? {
+        Some(pipeline) => Ok(pipeline),
+        None => Err("MongoDB aggregate operation requires a 'pipeline' array".to_string()),
+    }
+}
+
+fn find_options(spec: &Value) -> Result<FindOptions, String> {
+    // A limit of 0 asks for every document, like it does in the shell
+    let limit = match spec.get("limit") {
+        None | Some(Value::Null) => Some(DEFAULT_FIND_LIMIT),
+        Some(value) => match value.as_i64() {
+            Some(0) => None,
+            Some(limit) => Some(limit),
+            None => return Err("'limit' must be an integer".to_string()),
+        },
+    };
+    let skip = match spec.get("skip") {
+        None | Some(Value::Null) => None,
+        Some(value) => match value.as_u64() {
+            Some(skip) => Some(skip),
+            None => return Err("'skip' must be a non-negative integer".to_string()),
+        },
+    };
+    let batch_size = match spec.get("batchSize") {
+        None | Some(Value::Null) => None,
+        Some(value) => match value.as_u64().map(u32::try_from) {
+            Some(Ok(size)) => Some(size),
+            _ => return Err(format!("Failed to parse find options: 'batchSize' must be an integer from 0 to {}", u32::MAX)),
+        },
+    };
+    
+    Ok(FindOptions::builder()
+        .projection(parse_document(spec, "projection")?)
+        .sort(parse_document(spec, "sort")?)
+        .limit(limit)
+        .skip(skip)
+        .hint(parse_option::<Hint>(spec, "hint")?)
+        .collation(parse_option::<Collation>(spec, "collation")?)
+        .max_time(spec.get("maxTimeMS").and_then(|v| v.as_u64()).map(Duration::from_millis))
+        .batch_size(batch_size)
+        .build())
+}
+
+fn aggregate_options(spec: &Value) -> Result<AggregateOptions, String> {
+    Ok(AggregateOptions::builder()
+        .allow_disk_use(spec.get("allowDiskUse").and_then(|v| v.as_bool()))
+        .collation(parse_option::<Collation>(spec, "collation")?)
+        .hint(parse_option::<Hint>(spec, "hint")?)
+        .max_time(spec.get("maxTimeMS").and_then(|v| v.as_u64()).map(Duration::from_millis))
+        .build())
+}
+
+// Reads an optional field into a driver option type, e.g. a collation document or a hint
+// given as either an index name or a key pattern
+fn parse_option<T: DeserializeOwned>(spec: &Value, field: &str) -> Result<Option<T>, String> {
+    match spec.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(option) => Ok(Some(option)),
+            Err(e) => Err(format!("Failed to parse {}: {}", field, e)),
+        },
+    }
+}
+
+// Queries are either the JSON operation model or mongosh syntax such as db.users.find({})
+fn parse_query(client: &Client, query: &str) -> Result<Value, String> {
+    if shell::is_shell_query(query) {
+        // Shell queries run against the connection string's database, like mongosh
+        let default_db = client.default_database().map(|db| db.name().to_string());
+        return shell::parse(query, default_db.as_deref());
+    }
+    
+    match serde_json::from_str(query) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Failed to parse MongoDB query as JSON: {}", e)),
+    }
+}
+
+fn get_collection(client: &Client, query_json: &Value) -> Result<Collection<Document>, String> {
+    // Extract database and collection names
+    let db_name = match query_json.get("db").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => return Err("MongoDB query must include a 'db' field".to_string()),
     };
     
+    let collection_name = match query_json.get("collection").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => return Err("MongoDB query must include a 'collection' field".to_string()),
+    };
+    
+    Ok(client.database(db_name).collection::<Document>(collection_name))
+}
+
+fn get_client(connection_id: &str) -> Result<Client, String> {
+    // Clone the client to avoid holding the MutexGuard across await points
+    let connections = MONGO_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    match connections.get(connection_id) {
+        Some(client) => Ok(client.clone()),
+        None => Err(format!("Connection with ID {} not found", connection_id)),
+    }
+}
+
+pub async fn list_databases(connection_id: &str) -> Result<Vec<String>, String> {
+    let client = get_client(connection_id)?;
+    
     // List all databases
     let db_names = match client.list_database_names(None, None).await {
         Ok(names) => names,
@@ -200,14 +732,7 @@ pub async fn list_databases(connection_id: &str) -> Result<Vec<String>, String>
 }
 
 pub async fn list_collections(connection_id: &str, db_name: &str) -> Result<Vec<String>, String> {
-    // Get a clone of the client to avoid MutexGuard across await points
-    let client = {
-        let connections = MONGO_CONNECTIONS.lock().map_err(|e| e.to_string())?;
-        match connections.get(connection_id) {
-            Some(client) => client.clone(),
-            None => return Err(format!("Connection with ID {} not found", connection_id)),
-        }
-    };
+    let client = get_client(connection_id)?;
     
     // Get the database
     let db = client.database(db_name);
@@ -0,0 +1,167 @@
+use crate::database::models::MongoIndexInfo;
+use super::{bson_to_json, collect_documents, get_client, json_to_document, parse_option, JsonMode};
+use futures::StreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::{Collation, IndexOptions};
+use mongodb::{Collection, IndexModel};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub async fn list_indexes(connection_id: &str, db_name: &str, collection_name: &str) -> Result<Vec<MongoIndexInfo>, String> {
+    let client = get_client(connection_id)?;
+    let collection = client.database(db_name).collection::<Document>(collection_name);
+    
+    let mut cursor = match collection.list_indexes(None).await {
+        Ok(cursor) => cursor,
+        Err(e) => return Err(format!("Failed to list indexes: {}", e)),
+    };
+    let mut models = Vec::new();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(model) => models.push(model),
+            Err(e) => return Err(format!("Error while iterating MongoDB cursor: {}", e)),
+        }
+    }
+    
+    // Usage counts need the indexStats privilege; without it the indexes are listed without them
+    let usage = index_usage(&collection).await.unwrap_or_default();
+    
+    Ok(models.into_iter().map(|model| {
+        let options = model.options.unwrap_or_default();
+        let name = options.name.clone().unwrap_or_default();
+        let (usage_count, usage_since) = match usage.get(&name) {
+            Some((count, since)) => (Some(*count), since.clone()),
+            None => (None, None),
+        };
+        MongoIndexInfo {
+            kind: index_kind(&model.keys).to_string(),
+            keys: bson_to_json(&Bson::Document(model.keys), JsonMode::Relaxed),
+            name,
+            unique: options.unique.unwrap_or(false),
+            sparse: options.sparse.unwrap_or(false),
+            hidden: options.hidden.unwrap_or(false),
+            ttl_seconds: options.expire_after.map(|ttl| ttl.as_secs()),
+            partial_filter: options.partial_filter_expression.map(|filter| bson_to_json(&Bson::Document(filter), JsonMode::Relaxed)),
+            usage_count,
+            usage_since,
+        }
+    }).collect())
+}
+
+// Keys and options use the shell's createIndex spelling, e.g. {"location": "2dsphere"} and
+// {"unique": true, "expireAfterSeconds": 3600, "partialFilterExpression": {...}}
+pub async fn create_index(
+    connection_id: &str,
+    db_name: &str,
+    collection_name: &str,
+    keys: &Value,
+    options: Option<&Value>,
+) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let collection = client.database(db_name).collection::<Document>(collection_name);
+    
+    let keys = json_to_document(keys, "keys")?;
+    if keys.is_empty() {
+        return Err("An index needs at least one key".to_string());
+    }
+    
+    let index_options = match options {
+        Some(options) => index_options(options)?,
+        None => IndexOptions::default(),
+    };
+    let model = IndexModel::builder().keys(keys).options(index_options).build();
+    
+    match collection.create_index(model, None).await {
+        Ok(result) => Ok(result.index_name),
+        Err(e) => Err(format!("Failed to create index: {}", e)),
+    }
+}
+
+pub async fn drop_index(connection_id: &str, db_name: &str, collection_name: &str, name: &str) -> Result<(), String> {
+    let client = get_client(connection_id)?;
+    let collection = client.database(db_name).collection::<Document>(collection_name);
+    
+    match collection.drop_index(name, None).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to drop index {}: {}", name, e)),
+    }
+}
+
+fn index_options(options: &Value) -> Result<IndexOptions, String> {
+    let document = |field: &str| -> Result<Option<Document>, String> {
+        match options.get(field) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => json_to_document(value, field).map(Some),
+        }
+    };
+    
+    Ok(IndexOptions::builder()
+        .name(options.get("name").and_then(|v| v.as_str()).map(|name| name.to_string()))
+        .unique(options.get("unique").and_then(|v| v.as_bool()))
+        .sparse(options.get("sparse").and_then(|v| v.as_bool()))
+        .hidden(options.get("hidden").and_then(|v| v.as_bool()))
+        // TTL indexes remove documents once their date field is older than this
+        .expire_after(options.get("expireAfterSeconds").and_then(|v| v.as_u64()).map(Duration::from_secs))
+        .partial_filter_expression(document("partialFilterExpression")?)
+        .weights(document("weights")?)
+        .default_language(options.get("default_language").and_then(|v| v.as_str()).map(|language| language.to_string()))
+        .wildcard_projection(document("wildcardProjection")?)
+        .collation(parse_option::<Collation>(options, "collation")?)
+        .build())
+}
+
+// Operation counts per index name, summed over every host reporting the index
+async fn index_usage(collection: &Collection<Document>) -> Result<HashMap<String, (i64, Option<String>)>, String> {
+    let cursor = match collection.aggregate(vec![doc! {"$indexStats": {}}], None).await {
+        Ok(cursor) => cursor,
+        Err(e) => return Err(format!("Failed to load index statistics: {}", e)),
+    };
+    
+    let mut usage: HashMap<String, (i64, Option<String>)> = HashMap::new();
+    for stats in collect_documents(cursor).await? {
+        let name = match stats.get_str("name") {
+            Ok(name) => name.to_string(),
+            Err(_) => continue,
+        };
+        let accesses = stats.get_document("accesses").ok();
+        let ops = match accesses.and_then(|a| a.get("ops")) {
+            Some(Bson::Int64(ops)) => *ops,
+            Some(Bson::Int32(ops)) => *ops as i64,
+            _ => 0,
+        };
+        let since = accesses
+            .and_then(|a| a.get_datetime("since").ok())
+            .and_then(|since| since.try_to_rfc3339_string().ok());
+        
+        let entry = usage.entry(name).or_insert((0, None));
+        entry.0 += ops;
+        // Keep the earliest start of tracking across hosts
+        if let Some(since) = since {
+            if entry.1.as_ref().is_none_or(|earliest| &since < earliest) {
+                entry.1 = Some(since);
+            }
+        }
+    }
+    
+    Ok(usage)
+}
+
+// Special index types are named by the key's value, wildcard indexes by a $** key
+fn index_kind(keys: &Document) -> &'static str {
+    for (key, value) in keys {
+        if key == "$**" || key.ends_with(".$**") {
+            return "wildcard";
+        }
+        if let Bson::String(kind) = value {
+            return match kind.as_str() {
+                "text" => "text",
+                "2dsphere" => "2dsphere",
+                "2d" => "2d",
+                "hashed" => "hashed",
+                _ => "regular",
+            };
+        }
+    }
+    "regular"
+}
@@ -0,0 +1,145 @@
+use crate::database::models::{CollectionSchema, FieldSchema, FieldTypeCount};
+use super::{bson_to_json, collect_documents, get_client, JsonMode};
+use mongodb::bson::{doc, Bson, Document};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Example values kept per field
+const MAX_EXAMPLES: usize = 3;
+
+#[derive(Default)]
+struct FieldStats {
+    // Sampled documents containing the field at least once
+    documents: u64,
+    last_document: Option<usize>,
+    occurrences: u64,
+    nulls: u64,
+    types: Vec<(&'static str, u64)>,
+    examples: Vec<Value>,
+}
+
+pub async fn infer_schema(
+    connection_id: &str,
+    db_name: &str,
+    collection_name: &str,
+    sample_size: i64,
+) -> Result<CollectionSchema, String> {
+    if sample_size < 1 {
+        return Err("Sample size must be at least 1".to_string());
+    }
+    
+    let client = get_client(connection_id)?;
+    let collection = client.database(db_name).collection::<Document>(collection_name);
+    
+    let cursor = match collection.aggregate(vec![doc! {"$sample": {"size": sample_size}}], None).await {
+        Ok(cursor) => cursor,
+        Err(e) => return Err(format!("Failed to sample collection {}: {}", collection_name, e)),
+    };
+    let documents = collect_documents(cursor).await?;
+    
+    // Fields keep the order they were first seen in
+    let mut fields: Vec<(String, FieldStats)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (index, document) in documents.iter().enumerate() {
+        for (key, value) in document {
+            visit(key.clone(), value, index, &mut fields, &mut positions);
+        }
+    }
+    
+    let sampled = documents.len() as u64;
+    Ok(CollectionSchema {
+        collection: collection_name.to_string(),
+        sampled_documents: sampled,
+        fields: fields.into_iter().map(|(path, mut stats)| {
+            // Most common type first
+            stats.types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            FieldSchema {
+                path,
+                types: stats.types.iter().map(|(bson_type, count)| FieldTypeCount {
+                    bson_type: bson_type.to_string(),
+                    count: *count,
+                    frequency: *count as f64 / stats.occurrences as f64,
+                }).collect(),
+                occurrences: stats.occurrences,
+                null_rate: stats.nulls as f64 / stats.occurrences as f64,
+                missing_rate: 1.0 - stats.documents as f64 / sampled as f64,
+                examples: stats.examples,
+            }
+        }).collect(),
+    })
+}
+
+// Records a value at path, then descends into subdocuments ("a.b") and array elements ("a[]")
+fn visit(
+    path: String,
+    value: &Bson,
+    document: usize,
+    fields: &mut Vec<(String, FieldStats)>,
+    positions: &mut HashMap<String, usize>,
+) {
+    let position = *positions.entry(path.clone()).or_insert_with(|| {
+        fields.push((path.clone(), FieldStats::default()));
+        fields.len() - 1
+    });
+    let stats = &mut fields[position].1;
+    
+    if stats.last_document != Some(document) {
+        stats.last_document = Some(document);
+        stats.documents += 1;
+    }
+    stats.occurrences += 1;
+    
+    let bson_type = type_name(value);
+    match stats.types.iter_mut().find(|(t, _)| *t == bson_type) {
+        Some((_, count)) => *count += 1,
+        None => stats.types.push((bson_type, 1)),
+    }
+    
+    match value {
+        Bson::Null => stats.nulls += 1,
+        Bson::Document(document_value) => {
+            for (key, child) in document_value {
+                visit(format!("{}.{}", path, key), child, document, fields, positions);
+            }
+        },
+        Bson::Array(elements) => {
+            for element in elements {
+                visit(format!("{}[]", path), element, document, fields, positions);
+            }
+        },
+        // Containers are described by their children, so only scalars make examples
+        _ => {
+            let example = bson_to_json(value, JsonMode::Relaxed);
+            if stats.examples.len() < MAX_EXAMPLES && !stats.examples.contains(&example) {
+                stats.examples.push(example);
+            }
+        },
+    }
+}
+
+// Type names as used by the $type query operator
+fn type_name(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Document(_) => "object",
+        Bson::Array(_) => "array",
+        Bson::Binary(_) => "binData",
+        Bson::Undefined => "undefined",
+        Bson::ObjectId(_) => "objectId",
+        Bson::Boolean(_) => "bool",
+        Bson::DateTime(_) => "date",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::DbPointer(_) => "dbPointer",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::Symbol(_) => "symbol",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::Int32(_) => "int",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Int64(_) => "long",
+        Bson::Decimal128(_) => "decimal",
+        Bson::MinKey => "minKey",
+        Bson::MaxKey => "maxKey",
+    }
+}
@@ -0,0 +1,575 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+
+// Collection methods that map onto execute_query operations
+const OPERATIONS: &[&str] = &[
+    "find", "findOne", "insertOne", "insertMany", "updateOne", "updateMany", "replaceOne",
+    "deleteOne", "deleteMany", "findOneAndUpdate", "findOneAndReplace", "findOneAndDelete",
+    "countDocuments", "estimatedDocumentCount", "distinct", "bulkWrite", "aggregate",
+];
+
+// Shell expressions start with the db object; anything else is treated as a JSON query
+pub fn is_shell_query(query: &str) -> bool {
+    let query = query.trim_start();
+    query.starts_with("db.") || query.starts_with("db[")
+}
+
+// A property access or method call in a chain like db.users.find({}).limit(5)
+struct Segment {
+    name: String,
+    args: Option<Vec<Value>>,
+}
+
+// Translates a mongosh expression into the JSON operation model used by execute_query.
+// Shell constructors such as ObjectId() and ISODate() become Extended JSON values.
+pub fn parse(query: &str, default_db: Option<&str>) -> Result<Value, String> {
+    let mut parser = Parser { chars: query.chars().collect(), pos: 0 };
+    let segments = parser.chain()?;
+    
+    let mut segments = segments.into_iter().peekable();
+    match segments.next() {
+        Some(Segment { ref name, args: None }) if name == "db" => {},
+        _ => return Err("Shell queries must start with db".to_string()),
+    }
+    
+    // db.getSiblingDB("name") switches databases, db.getCollection("name") reaches
+    // collections whose names aren't valid identifiers
+    let mut db_name = default_db.map(|name| name.to_string());
+    if let Some(Segment { name, args: Some(args) }) = segments.peek() {
+        if name == "getSiblingDB" {
+            db_name = Some(string_argument(args, "getSiblingDB")?);
+            segments.next();
+        }
+    }
+    let db_name = match db_name {
+        Some(name) => name,
+        None => return Err("No database selected: add one to the connection string or use db.getSiblingDB(\"name\")".to_string()),
+    };
+    let collection_name = match segments.next() {
+        Some(Segment { name, args: Some(args) }) if name == "getCollection" => string_argument(&args, "getCollection")?,
+        Some(Segment { name, args: None }) => name,
+        Some(Segment { name, .. }) => return Err(format!("Unsupported database method: {}", name)),
+        None => return Err("Shell query must name a collection".to_string()),
+    };
+    
+    let (operation, args) = match segments.next() {
+        Some(Segment { name, args: Some(args) }) => (name, args),
+        Some(Segment { name, args: None }) => return Err(format!("Expected a method call after {}.{}", collection_name, name)),
+        None => return Err(format!("Shell query must call a method on {}", collection_name)),
+    };
+    if !OPERATIONS.contains(&operation.as_str()) {
+        return Err(format!("Unsupported collection method: {}", operation));
+    }
+    
+    let mut query_json = Map::new();
+    query_json.insert("db".to_string(), json!(db_name));
+    query_json.insert("collection".to_string(), json!(collection_name));
+    query_json.insert("operation".to_string(), json!(operation));
+    
+    // Positional arguments of each method, in shell order; a trailing options object is merged in
+    let positions: &[&str] = match operation.as_str() {
+        "find" | "findOne" => &["filter", "projection"],
+        "insertOne" => &["document"],
+        "insertMany" => &["documents"],
+        "updateOne" | "updateMany" | "findOneAndUpdate" => &["filter", "update"],
+        "replaceOne" | "findOneAndReplace" => &["filter", "replacement"],
+        "deleteOne" | "deleteMany" | "findOneAndDelete" | "countDocuments" => &["filter"],
+        "distinct" => &["field", "filter"],
+        "bulkWrite" => &["operations"],
+        "aggregate" => &["pipeline"],
+        _ => &[],
+    };
+    for (i, arg) in args.into_iter().enumerate() {
+        match positions.get(i) {
+            Some(field) => {
+                query_json.insert(field.to_string(), arg);
+            },
+            None => match arg {
+                Value::Object(options) => query_json.extend(options),
+                _ => return Err(format!("Too many arguments to {}", operation)),
+            },
+        }
+    }
+    
+    if operation == "findOne" {
+        query_json.insert("operation".to_string(), json!("find"));
+        query_json.insert("limit".to_string(), json!(1));
+    }
+    
+    // mongosh's returnNewDocument is the older spelling of returnDocument: "after"
+    if let Some(return_new) = query_json.remove("returnNewDocument") {
+        let return_document = if return_new.as_bool().unwrap_or(false) { "after" } else { "before" };
+        query_json.insert("returnDocument".to_string(), json!(return_document));
+    }
+    
+    // Cursor methods chained after find or aggregate
+    for segment in segments {
+        let args = match segment.args {
+            Some(args) => args,
+            None => return Err(format!("Expected a method call: {}", segment.name)),
+        };
+        let first = args.into_iter().next();
+        match (operation.as_str(), segment.name.as_str()) {
+            (_, "toArray" | "pretty" | "itcount") => {},
+            ("find", "sort" | "limit" | "skip" | "hint" | "collation" | "maxTimeMS" | "batchSize" | "projection") => {
+                let value = first.ok_or_else(|| format!("{}() requires an argument", segment.name))?;
+                query_json.insert(segment.name, value);
+            },
+            ("find", "count") => {
+                query_json.insert("operation".to_string(), json!("countDocuments"));
+            },
+            ("aggregate", "allowDiskUse" | "hint" | "collation" | "maxTimeMS") => {
+                let value = first.unwrap_or(Value::Bool(true));
+                query_json.insert(segment.name, value);
+            },
+            (_, method) => return Err(format!("Unsupported cursor method {} after {}", method, operation)),
+        }
+    }
+    
+    Ok(Value::Object(query_json))
+}
+
+fn string_argument(args: &[Value], method: &str) -> Result<String, String> {
+    match args.first() {
+        Some(Value::String(s)) => Ok(s.clone()),
+        _ => Err(format!("{}() requires a string argument", method)),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn chain(&mut self) -> Result<Vec<Segment>, String> {
+        let mut segments = vec![Segment { name: self.identifier()?, args: None }];
+        
+        loop {
+            self.skip_whitespace();
+            let name = match self.peek() {
+                Some('.') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    self.identifier()?
+                },
+                Some('[') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(']')?;
+                    name
+                },
+                Some(';') | None => break,
+                Some(c) => return Err(self.error(&format!("unexpected '{}'", c))),
+            };
+            
+            self.skip_whitespace();
+            let args = if self.peek() == Some('(') {
+                self.pos += 1;
+                Some(self.list(')')?)
+            } else {
+                None
+            };
+            segments.push(Segment { name, args });
+        }
+        
+        // Only a trailing semicolon may follow the expression
+        if self.peek() == Some(';') {
+            self.pos += 1;
+        }
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return Err(self.error("unexpected input after the query"));
+        }
+        
+        Ok(segments)
+    }
+    
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.object()
+            },
+            Some('[') => {
+                self.pos += 1;
+                Ok(Value::Array(self.list(']')?))
+            },
+            Some('"') | Some('\'') => Ok(Value::String(self.string()?)),
+            Some('/') => self.regex(),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => self.number(),
+            Some(c) if is_identifier_start(c) => {
+                let mut name = self.identifier()?;
+                if name == "new" {
+                    self.skip_whitespace();
+                    name = self.identifier()?;
+                }
+                match name.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" | "undefined" => Ok(Value::Null),
+                    _ => self.constructor(&name),
+                }
+            },
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of query")),
+        }
+    }
+    
+    // Relaxed object literal: unquoted or single-quoted keys and trailing commas are allowed
+    fn object(&mut self) -> Result<Value, String> {
+        let mut object = Map::new();
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                },
+                Some('"') | Some('\'') => self.string()?,
+                Some(c) if c.is_ascii_digit() => self.number()?.to_string(),
+                _ => self.identifier()?,
+            };
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            object.insert(key, value);
+            
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {},
+                Some('}') => break,
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+        Ok(Value::Object(object))
+    }
+    
+    fn list(&mut self, close: char) -> Result<Vec<Value>, String> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                break;
+            }
+            values.push(self.value()?);
+            
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {},
+                Some(c) if c == close => break,
+                _ => return Err(self.error(&format!("expected ',' or '{}'", close))),
+            }
+        }
+        Ok(values)
+    }
+    
+    // Shell helpers for BSON types, written out as their Extended JSON equivalents
+    fn constructor(&mut self, name: &str) -> Result<Value, String> {
+        self.skip_whitespace();
+        self.expect('(')?;
+        let args = self.list(')')?;
+        let first = args.first();
+        
+        // Numeric helpers accept both numbers and numeric strings
+        let text = |value: Option<&Value>| -> Option<String> {
+            match value {
+                Some(Value::String(s)) => Some(s.clone()),
+                Some(Value::Number(n)) => Some(n.to_string()),
+                _ => None,
+            }
+        };
+        let missing = || format!("{}() requires an argument", name);
+        
+        match name {
+            "ObjectId" => match first {
+                Some(Value::String(id)) => Ok(json!({ "$oid": id })),
+                // A new id is generated when none is given, as in the shell
+                None => Ok(json!({ "$oid": mongodb::bson::oid::ObjectId::new().to_hex() })),
+                _ => Err("ObjectId() requires a hex string".to_string()),
+            },
+            "ISODate" | "Date" => match first {
+                Some(Value::String(date)) => Ok(json!({ "$date": normalize_date(date)? })),
+                Some(Value::Number(millis)) => Ok(json!({ "$date": { "$numberLong": millis.to_string() } })),
+                None => Ok(json!({ "$date": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true) })),
+                _ => Err(format!("Invalid argument to {}()", name)),
+            },
+            "NumberLong" | "Long" => Ok(json!({ "$numberLong": text(first).ok_or_else(missing)? })),
+            "NumberInt" | "Int32" => Ok(json!({ "$numberInt": text(first).ok_or_else(missing)? })),
+            "NumberDecimal" | "Decimal128" => Ok(json!({ "$numberDecimal": text(first).ok_or_else(missing)? })),
+            "UUID" => match first {
+                Some(Value::String(uuid)) => Ok(json!({ "$uuid": uuid })),
+                None => Ok(json!({ "$uuid": uuid::Uuid::new_v4().to_string() })),
+                _ => Err("UUID() requires a string".to_string()),
+            },
+            "Timestamp" => match (args.first(), args.get(1)) {
+                (Some(t), Some(i)) => Ok(json!({ "$timestamp": { "t": t, "i": i } })),
+                _ => Err("Timestamp() requires a time and an increment".to_string()),
+            },
+            "BinData" => match (args.first().and_then(Value::as_u64), args.get(1)) {
+                (Some(subtype), Some(Value::String(data))) => Ok(json!({
+                    "$binary": { "base64": data, "subType": format!("{:02x}", subtype) }
+                })),
+                _ => Err("BinData() requires a subtype and a base64 string".to_string()),
+            },
+            _ => Err(self.error(&format!("unknown identifier {}", name))),
+        }
+    }
+    
+    fn string(&mut self) -> Result<String, String> {
+        let quote = match self.next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Err(self.error("expected a string")),
+        };
+        
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) => s.push(c),
+                            None => return Err(self.error("invalid unicode escape")),
+                        }
+                    },
+                    Some(c) => s.push(c),
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+    
+    // Regex literals such as /^abc/i
+    fn regex(&mut self) -> Result<Value, String> {
+        self.expect('/')?;
+        let mut pattern = String::new();
+        loop {
+            match self.next() {
+                Some('/') => break,
+                Some('\\') => {
+                    pattern.push('\\');
+                    match self.next() {
+                        Some(c) => pattern.push(c),
+                        None => return Err(self.error("unterminated regular expression")),
+                    }
+                },
+                Some(c) => pattern.push(c),
+                None => return Err(self.error("unterminated regular expression")),
+            }
+        }
+        
+        let mut options = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphabetic()) {
+            options.push(c);
+            self.pos += 1;
+        }
+        
+        Ok(json!({ "$regularExpression": { "pattern": pattern, "options": options } }))
+    }
+    
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.')) {
+            // A sign is only part of the number at the start or after an exponent
+            if (c == '-' || c == '+') && self.pos > start && !matches!(self.chars[self.pos - 1], 'e' | 'E') {
+                break;
+            }
+            self.pos += 1;
+        }
+        
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let text = text.strip_prefix('+').unwrap_or(&text);
+        if let Ok(n) = text.parse::<i64>() {
+            return Ok(json!(n));
+        }
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(json!(n)),
+            _ => Err(self.error(&format!("invalid number {}", text))),
+        }
+    }
+    
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if is_identifier_start(c) => self.pos += 1,
+            _ => return Err(self.error("expected an identifier")),
+        }
+        while self.peek().filter(|c| is_identifier_start(*c) || c.is_ascii_digit()).is_some() {
+            self.pos += 1;
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+    
+    // Skips whitespace and JavaScript comments
+    fn skip_whitespace(&mut self) {
+        loop {
+            match (self.peek(), self.chars.get(self.pos + 1)) {
+                (Some(c), _) if c.is_whitespace() => self.pos += 1,
+                (Some('/'), Some('/')) => {
+                    while !matches!(self.peek(), Some('\n') | None) {
+                        self.pos += 1;
+                    }
+                },
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len() && !(self.chars[self.pos - 1] == '*' && self.chars[self.pos] == '/') {
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                },
+                _ => break,
+            }
+        }
+    }
+    
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+    
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+    
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+    
+    fn error(&self, message: &str) -> String {
+        format!("Failed to parse shell query at position {}: {}", self.pos, message)
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+// The shell accepts dates without a time or zone; Extended JSON needs a full RFC 3339 timestamp
+fn normalize_date(date: &str) -> Result<String, String> {
+    let parsed = DateTime::parse_from_rfc3339(date)
+        .map(|d| d.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S%.f").map(|d| d.and_utc()))
+        .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M").map(|d| d.and_utc()))
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()));
+    
+    match parsed {
+        Ok(date) => Ok(date.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        Err(_) => Err(format!("Invalid date: {}", date)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parse_ok(query: &str) -> Value {
+        parse(query, Some("app")).unwrap()
+    }
+    
+    #[test]
+    fn translates_find_with_cursor_methods() {
+        assert_eq!(
+            parse_ok("db.users.find({age:{$gt:30}}).sort({name:1}).limit(10)"),
+            json!({
+                "db": "app",
+                "collection": "users",
+                "operation": "find",
+                "filter": { "age": { "$gt": 30 } },
+                "sort": { "name": 1 },
+                "limit": 10,
+            })
+        );
+    }
+    
+    #[test]
+    fn detects_shell_queries() {
+        assert!(is_shell_query("  db.users.find()"));
+        assert!(is_shell_query("db['my-coll'].find()"));
+        assert!(!is_shell_query(r#"{"operation": "find"}"#));
+    }
+    
+    #[test]
+    fn converts_shell_constructors_to_extended_json() {
+        let query = parse_ok("db.c.insertOne({
+            _id: ObjectId('5f1d7f3e9b1e8b3a2c4d5e6f'),
+            day: ISODate('2024-01-02'),
+            at: new Date('2024-01-02T03:04:05Z'),
+            epoch: Date(0),
+            long: NumberLong('5'),
+            int: NumberInt(7),
+            price: Decimal128('1.50'),
+            key: UUID('3b241101-e2bb-4255-8caf-4136c566a962'),
+            bin: BinData(0, 'AQID'),
+        })");
+        assert_eq!(query["document"], json!({
+            "_id": { "$oid": "5f1d7f3e9b1e8b3a2c4d5e6f" },
+            "day": { "$date": "2024-01-02T00:00:00.000Z" },
+            "at": { "$date": "2024-01-02T03:04:05.000Z" },
+            "epoch": { "$date": { "$numberLong": "0" } },
+            "long": { "$numberLong": "5" },
+            "int": { "$numberInt": "7" },
+            "price": { "$numberDecimal": "1.50" },
+            "key": { "$uuid": "3b241101-e2bb-4255-8caf-4136c566a962" },
+            "bin": { "$binary": { "base64": "AQID", "subType": "00" } },
+        }));
+    }
+    
+    #[test]
+    fn reads_regex_literals_and_skips_comments() {
+        let query = parse_ok("db.users.find({
+            // users named a...
+            name: /^a\\/b/i /* case-insensitive */
+        }) // trailing");
+        assert_eq!(query["filter"], json!({
+            "name": { "$regularExpression": { "pattern": "^a\\/b", "options": "i" } }
+        }));
+    }
+    
+    #[test]
+    fn switches_database_and_collection() {
+        let query = parse_ok("db.getSiblingDB('logs').getCollection('2024-events').countDocuments({})");
+        assert_eq!(query["db"], json!("logs"));
+        assert_eq!(query["collection"], json!("2024-events"));
+        assert_eq!(query["operation"], json!("countDocuments"));
+        
+        let query = parse(r#"db.getSiblingDB("logs")["events"].find()"#, None).unwrap();
+        assert_eq!(query["db"], json!("logs"));
+        assert_eq!(query["collection"], json!("events"));
+        
+        assert!(parse("db.users.find()", None).is_err());
+    }
+    
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in [
+            "db.users.find({age: })",
+            "db.users.find({age: 1}",
+            "db.users.find('unterminated)",
+            "db.users.find({name: /open})",
+            "db.users.find() extra",
+            "db.users.drop()",
+            "db.users.find().explode()",
+            "db.users.find(Foo(1))",
+            "db.users",
+            "users.find()",
+            "db.users.find({n: 1e999})",
+        ] {
+            assert!(parse(query, Some("app")).is_err(), "{} should not parse", query);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::database::{MONGO_WATCHES, generate_connection_id};
+use crate::database::models::MongoChangeEvent;
+use super::{bson_to_json, get_client, json_to_document, JsonMode};
+use futures::StreamExt;
+use mongodb::bson::{self, Bson, Document};
+use mongodb::change_stream::event::ResumeToken;
+use mongodb::options::{ChangeStreamOptions, FullDocumentType};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use tokio::task::JoinHandle;
+
+// Event emitted for each change seen by a running watch
+pub const CHANGE_EVENT: &str = "mongodb-change";
+
+// A spawned change stream and the connection it was opened through
+pub struct RunningWatch {
+    connection_id: String,
+    handle: JoinHandle<()>,
+}
+
+// Opens a change stream on a collection, or on the whole database when no collection is given,
+// and forwards its events until unwatch_mongodb stops it. Change streams need a replica set;
+// a single-node one started with --replSet works for local testing.
+pub async fn watch(
+    app: AppHandle,
+    connection_id: &str,
+    db_name: &str,
+    collection_name: Option<&str>,
+    pipeline: Option<&Value>,
+    resume_after: Option<&Value>,
+) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let database = client.database(db_name);
+    
+    let pipeline = match pipeline {
+        Some(Value::Array(stages)) => stages.iter()
+            .map(|stage| json_to_document(stage, "pipeline"))
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(Value::Null) | None => Vec::new(),
+        Some(_) => return Err("'pipeline' must be an array of documents".to_string()),
+    };
+    
+    // A token from an earlier event picks the stream up right after that event
+    let resume_after = match resume_after {
+        Some(Value::Null) | None => None,
+        Some(token) => {
+            let token = Bson::try_from(token.clone()).map_err(|e| format!("Invalid resume token: {}", e))?;
+            Some(bson::from_bson::<ResumeToken>(token).map_err(|e| format!("Invalid resume token: {}", e))?)
+        },
+    };
+    let options = ChangeStreamOptions::builder()
+        .full_document(Some(FullDocumentType::UpdateLookup))
+        .resume_after(resume_after)
+        .build();
+    
+    // Opening the stream up front reports errors such as a standalone server to the caller
+    let result = match collection_name {
+        Some(name) => database.collection::<Document>(name).watch(pipeline, options).await,
+        None => database.watch(pipeline, options).await,
+    };
+    let mut stream = match result {
+        Ok(stream) => stream.with_type::<Document>(),
+        Err(e) => return Err(format!("Failed to open change stream: {}", e)),
+    };
+    
+    let watch_id = generate_connection_id();
+    let task_watch_id = watch_id.clone();
+    
+    // Hold the lock while spawning so the task can't finish and unregister before it's registered
+    let mut watches = MONGO_WATCHES.lock().map_err(|e| e.to_string())?;
+    let handle = tokio::spawn(async move {
+        // The driver resumes once on its own after transient errors; anything else ends the watch
+        while let Some(result) = stream.next().await {
+            // Token for resuming right after this event
+            let resume_token = stream.resume_token()
+                .and_then(|token| bson::to_bson(&token).ok())
+                .map(|token| bson_to_json(&token, JsonMode::Relaxed));
+            let (event, error) = match result {
+                Ok(event) => (Some(bson_to_json(&Bson::Document(event), JsonMode::Relaxed)), None),
+                Err(e) => (None, Some(format!("Change stream failed: {}", e))),
+            };
+            let failed = error.is_some();
+            let payload = MongoChangeEvent {
+                watch_id: task_watch_id.clone(),
+                event,
+                resume_token,
+                error,
+            };
+            
+            if let Err(e) = app.emit(CHANGE_EVENT, payload) {
+                eprintln!("Failed to emit MongoDB change: {}", e);
+            }
+            if failed {
+                break;
+            }
+        }
+        
+        if let Ok(mut watches) = MONGO_WATCHES.lock() {
+            watches.remove(&task_watch_id);
+        }
+    });
+    watches.insert(watch_id.clone(), RunningWatch {
+        connection_id: connection_id.to_string(),
+        handle,
+    });
+    
+    Ok(watch_id)
+}
+
+pub fn stop_watch(watch_id: &str) -> Result<(), String> {
+    let mut watches = MONGO_WATCHES.lock().map_err(|e| e.to_string())?;
+    
+    match watches.remove(watch_id) {
+        Some(watch) => {
+            watch.handle.abort();
+            Ok(())
+        },
+        None => Err(format!("Watch with ID {} not found", watch_id)),
+    }
+}
+
+// Stops every watch opened through a connection, once that connection goes away
+pub fn stop_watches(connection_id: &str) -> Result<(), String> {
+    let mut watches = MONGO_WATCHES.lock().map_err(|e| e.to_string())?;
+    
+    watches.retain(|_, watch| {
+        if watch.connection_id != connection_id {
+            return true;
+        }
+        watch.handle.abort();
+        false
+    });
+    
+    Ok(())
+}
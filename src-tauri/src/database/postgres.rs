@@ -1,10 +1,41 @@
-use crate::database::{POSTGRES_CONNECTIONS, generate_connection_id};
-use crate::database::models::{ConnectionConfig, QueryResult};
-use tokio_postgres::NoTls;
+use crate::database::{POSTGRES_CONNECTIONS, POSTGRES_CONNECTION_STRINGS, POSTGRES_NOTICES, generate_connection_id};
+use crate::database::models::{
+    ConnectionConfig, QueryResult, DatabaseItem, DatabaseStructure, TableDescription, ColumnInfo,
+    ConstraintInfo, IndexInfo, PartitionInfo, PostgresNotification, PostgresNotice,
+};
+use futures::{stream, StreamExt};
+use tauri::{AppHandle, Emitter};
+use tokio_postgres::{AsyncMessage, Client, NoTls, Row};
+use tokio_postgres::types::{FromSql, Oid, Type};
 use serde_json::{Value, json, Map};
+use std::collections::BTreeMap;
+use std::error::Error;
 use std::sync::Arc;
 
-pub async fn connect_postgres(config: &ConnectionConfig) -> Result<String, String> {
+pub mod activity;
+pub mod copy;
+pub mod ddl;
+pub mod explain;
+pub mod extensions;
+pub mod functions;
+pub mod geo;
+pub mod roles;
+pub mod stats;
+
+// Event emitted for every NOTIFY received on a channel the connection listens to
+pub const POSTGRES_NOTIFICATION_EVENT: &str = "postgres-notification";
+
+// Explorer groups under each schema: (object kind, group label)
+const SCHEMA_GROUPS: &[(&str, &str)] = &[
+    ("table", "Tables"),
+    ("view", "Views"),
+    ("materialized_view", "Materialized Views"),
+    ("function", "Functions"),
+    ("sequence", "Sequences"),
+    ("type", "Types"),
+];
+
+pub async fn connect_postgres(app: AppHandle, config: &ConnectionConfig) -> Result<String, String> {
     let connection_string = match &config.connection_string {
         Some(uri) => uri.clone(),
         None => {
@@ -31,7 +62,7 @@ pub async fn connect_postgres(config: &ConnectionConfig) -> Result<String, Strin
     };
     
     // Connect to the database
-    let (client, connection) = match tokio_postgres::connect(&connection_string, NoTls).await {
+    let (client, mut connection) = match tokio_postgres::connect(&connection_string, NoTls).await {
         Ok((client, connection)) => (client, connection),
         Err(e) => return Err(format!("Failed to connect to PostgreSQL: {}", e)),
     };
@@ -39,22 +70,96 @@ pub async fn connect_postgres(config: &ConnectionConfig) -> Result<String, Strin
     // Wrap the client in an Arc to make it shareable
     let client = Arc::new(client);
     
-    // Spawn a task to drive the connection to completion
+    let connection_id = generate_connection_id();
+    
+    // Spawn a task to drive the connection, forwarding notifications and collecting notices as they arrive
+    let notification_id = connection_id.clone();
     tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("PostgreSQL connection error: {}", e);
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            match message {
+                Ok(AsyncMessage::Notification(notification)) => {
+                    let payload = PostgresNotification {
+                        connection_id: notification_id.clone(),
+                        channel: notification.channel().to_string(),
+                        payload: notification.payload().to_string(),
+                        process_id: notification.process_id(),
+                    };
+                    if let Err(e) = app.emit(POSTGRES_NOTIFICATION_EVENT, payload) {
+                        eprintln!("Failed to emit PostgreSQL notification: {}", e);
+                    }
+                },
+                Ok(AsyncMessage::Notice(notice)) => {
+                    let notice = PostgresNotice {
+                        severity: notice.severity().to_string(),
+                        code: notice.code().code().to_string(),
+                        message: notice.message().to_string(),
+                        detail: notice.detail().map(str::to_string),
+                        hint: notice.hint().map(str::to_string),
+                    };
+                    if let Ok(mut notices) = POSTGRES_NOTICES.lock() {
+                        notices.entry(notification_id.clone()).or_default().push(notice);
+                    }
+                },
+                Ok(_) => {},
+                Err(e) => {
+                    eprintln!("PostgreSQL connection error: {}", e);
+                    break;
+                },
+            }
         }
     });
     
-    let connection_id = generate_connection_id();
-    
     // Store the connection
     let mut connections = POSTGRES_CONNECTIONS.lock().map_err(|e| e.to_string())?;
     connections.insert(connection_id.clone(), client);
     
+    let mut connection_strings = POSTGRES_CONNECTION_STRINGS.lock().map_err(|e| e.to_string())?;
+    connection_strings.insert(connection_id.clone(), connection_string);
+    
     Ok(connection_id)
 }
 
+pub fn close_connection(connection_id: &str) -> Result<(), String> {
+    // The activity monitor holds its own reference to the client, so stop it first
+    let _ = activity::stop_monitor(connection_id);
+    
+    POSTGRES_NOTICES.lock().map_err(|e| e.to_string())?.remove(connection_id);
+    POSTGRES_CONNECTION_STRINGS.lock().map_err(|e| e.to_string())?.remove(connection_id);
+    
+    let mut connections = POSTGRES_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    if connections.remove(connection_id).is_some() {
+        Ok(())
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
+// Opens a separate session to the same server, for work such as transactions that must not
+// interleave with queries running on the shared client
+async fn open_session(connection_id: &str) -> Result<Client, String> {
+    let connection_string = {
+        let connection_strings = POSTGRES_CONNECTION_STRINGS.lock().map_err(|e| e.to_string())?;
+        match connection_strings.get(connection_id) {
+            Some(connection_string) => connection_string.clone(),
+            None => return Err(format!("Connection with ID {} not found", connection_id)),
+        }
+    };
+    
+    let (client, connection) = match tokio_postgres::connect(&connection_string, NoTls).await {
+        Ok((client, connection)) => (client, connection),
+        Err(e) => return Err(format!("Failed to connect to PostgreSQL: {}", e)),
+    };
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("PostgreSQL connection error: {}", e);
+        }
+    });
+    
+    Ok(client)
+}
+
 pub async fn execute_query(connection_id: String, query: String) -> Result<QueryResult, String> {
     // Get the client reference without holding the lock for too long
     let client_ref = {
@@ -69,12 +174,17 @@ pub async fn execute_query(connection_id: String, query: String) -> Result<Query
     // Now we can use the Arc<Client> outside the mutex lock
     let client = &*client_ref;
     
+    // Drop notices left over from earlier queries so only this query's are returned
+    take_notices(&connection_id)?;
+    
     // Check if the query is a SELECT statement to determine how to handle it
     let query_lowercase = query.trim().to_lowercase();
     let is_select = query_lowercase.starts_with("select") || 
                     query_lowercase.starts_with("with") ||
                     query_lowercase.starts_with("show") ||
-                    query_lowercase.starts_with("explain");
+                    query_lowercase.starts_with("explain") ||
+                    // CALL returns a row holding the procedure's OUT arguments
+                    query_lowercase.starts_with("call");
     
     if is_select {
         // Execute a SELECT query
@@ -96,42 +206,14 @@ pub async fn execute_query(connection_id: String, query: String) -> Result<Query
                     for (i, column) in row.columns().iter().enumerate() {
                         let column_name = column.name();
                         
-                        // Get the value based on PostgreSQL types
-                        let value = match row.try_get::<_, Option<&str>>(i) {
-                            Ok(Some(val)) => json!(val),
-                            Ok(None) => Value::Null,
-                            Err(_) => {
-                                // Try other types
-                                match row.try_get::<_, Option<i32>>(i) {
-                                    Ok(Some(val)) => json!(val),
-                                    Ok(None) => Value::Null,
-                                    Err(_) => {
-                                        match row.try_get::<_, Option<i64>>(i) {
-                                            Ok(Some(val)) => json!(val),
-                                            Ok(None) => Value::Null,
-                                            Err(_) => {
-                                                match row.try_get::<_, Option<f64>>(i) {
-                                                    Ok(Some(val)) => json!(val),
-                                                    Ok(None) => Value::Null,
-                                                    Err(_) => {
-                                                        match row.try_get::<_, Option<bool>>(i) {
-                                                            Ok(Some(val)) => json!(val),
-                                                            Ok(None) => Value::Null,
-                                                            Err(_) => {
-                                                                // Try to get as string representation for other types
-                                                                json!(format!("{:?}", row.get::<_, String>(i)))
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        };
+                        // PostGIS geometries arrive as EWKB and are decoded to GeoJSON
+                        if geo::is_spatial(column.type_()) {
+                            row_map.insert(column_name.to_string(), geo::geometry_cell(&row, i));
+                            continue;
+                        }
                         
-                        row_map.insert(column_name.to_string(), value);
+                        // Get the value based on PostgreSQL types
+                        row_map.insert(column_name.to_string(), row_value(&row, i));
                     }
                     
                     result_rows.push(Value::Object(row_map));
@@ -144,9 +226,10 @@ pub async fn execute_query(connection_id: String, query: String) -> Result<Query
                     affected_rows: Some(rows_len),
                     success: true,
                     error: None,
+                    notices: Some(take_notices(&connection_id)?),
                 })
             },
-            Err(e) => Err(format!("Failed to execute PostgreSQL query: {}", e)),
+            Err(e) => Err(query_error(&connection_id, e)?),
         }
     } else {
         // Execute a non-SELECT query
@@ -157,8 +240,9 @@ pub async fn execute_query(connection_id: String, query: String) -> Result<Query
                 affected_rows: Some(affected),
                 success: true,
                 error: None,
+                notices: Some(take_notices(&connection_id)?),
             }),
-            Err(e) => Err(format!("Failed to execute PostgreSQL query: {}", e)),
+            Err(e) => Err(query_error(&connection_id, e)?),
         }
     }
 }
@@ -226,4 +310,410 @@ pub async fn list_tables(connection_id: &str, schema: Option<&str>) -> Result<Ve
     }
     
     Ok(table_names)
-}
\ No newline at end of file
+}
+
+fn take_notices(connection_id: &str) -> Result<Vec<PostgresNotice>, String> {
+    let mut notices = POSTGRES_NOTICES.lock().map_err(|e| e.to_string())?;
+    Ok(notices.remove(connection_id).unwrap_or_default())
+}
+
+// Notices raised before a query failed are appended to its error, so they aren't lost or
+// returned with the next query instead
+fn query_error(connection_id: &str, error: tokio_postgres::Error) -> Result<String, String> {
+    let mut message = match error.as_db_error() {
+        Some(db_error) => format!("Failed to execute PostgreSQL query: {}", db_error),
+        None => format!("Failed to execute PostgreSQL query: {}", error),
+    };
+    for notice in take_notices(connection_id)? {
+        message.push_str(&format!("\n{}: {}", notice.severity, notice.message));
+    }
+    Ok(message)
+}
+
+// Converts a non-spatial column to JSON, trying the types the grid knows how to show
+fn row_value(row: &Row, i: usize) -> Value {
+    let column = &row.columns()[i];
+    
+    match row.try_get::<_, Option<&str>>(i) {
+        Ok(Some(val)) => json!(val),
+        Ok(None) => Value::Null,
+        Err(_) => {
+            // Try other types
+            match row.try_get::<_, Option<i32>>(i) {
+                Ok(Some(val)) => json!(val),
+                Ok(None) => Value::Null,
+                Err(_) => {
+                    match row.try_get::<_, Option<i64>>(i) {
+                        Ok(Some(val)) => json!(val),
+                        Ok(None) => Value::Null,
+                        Err(_) => {
+                            match row.try_get::<_, Option<f64>>(i) {
+                                Ok(Some(val)) => json!(val),
+                                Ok(None) => Value::Null,
+                                Err(_) => {
+                                    match row.try_get::<_, Option<bool>>(i) {
+                                        Ok(Some(val)) => json!(val),
+                                        Ok(None) => Value::Null,
+                                        Err(_) => {
+                                            // Types without a decoder here (void, numeric, ...) can't be read,
+                                            // so the cell names the type instead of failing the whole query
+                                            match row.try_get::<_, Option<Undecoded>>(i) {
+                                                Ok(Some(_)) if *column.type_() != Type::VOID => {
+                                                    json!(format!("<unsupported type {}>", column.type_().name()))
+                                                },
+                                                _ => Value::Null,
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Accepts a value of any type without decoding it, so NULLs can be told apart in columns that can't be read
+struct Undecoded;
+
+impl<'a> FromSql<'a> for Undecoded {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Undecoded)
+    }
+    
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+// Always quote so names keep their case and can't break out of the statement
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// Same escaping as the server's quote_literal, so backslashes survive standard_conforming_strings = off
+fn quote_literal(value: &str) -> String {
+    let quoted = value.replace('\'', "''");
+    if quoted.contains('\\') {
+        format!("E'{}'", quoted.replace('\\', "\\\\"))
+    } else {
+        format!("'{}'", quoted)
+    }
+}
+
+fn get_client(connection_id: &str) -> Result<Arc<Client>, String> {
+    let connections = POSTGRES_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    match connections.get(connection_id) {
+        Some(client) => Ok(Arc::clone(client)),
+        None => Err(format!("Connection with ID {} not found", connection_id)),
+    }
+}
+
+pub async fn list_schemas(connection_id: &str, include_system: bool) -> Result<Vec<String>, String> {
+    let client = get_client(connection_id)?;
+    
+    let query = "SELECT nspname FROM pg_namespace \
+                 WHERE $1 OR (nspname NOT LIKE 'pg\\_%' AND nspname <> 'information_schema') \
+                 ORDER BY nspname";
+    let rows = match client.query(query, &[&include_system]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to list schemas: {}", e)),
+    };
+    
+    Ok(rows.iter().filter_map(|row| row.try_get::<_, String>(0).ok()).collect())
+}
+
+pub async fn get_database_structure(connection_id: &str, include_system: bool) -> Result<DatabaseStructure, String> {
+    let client = get_client(connection_id)?;
+    
+    let database: String = match client.query_one("SELECT current_database()::text", &[]).await {
+        Ok(row) => row.get(0),
+        Err(e) => return Err(format!("Failed to get current database: {}", e)),
+    };
+    
+    // Collect every object in one round trip as (schema, kind, name) rows
+    let query = "
+        SELECT n.nspname AS schema, CASE c.relkind
+                   WHEN 'r' THEN 'table' WHEN 'p' THEN 'table'
+                   WHEN 'v' THEN 'view' WHEN 'm' THEN 'materialized_view'
+                   ELSE 'sequence' END AS kind,
+               c.relname::text AS name
+        FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'p', 'v', 'm', 'S') AND NOT c.relispartition
+        UNION ALL
+        SELECT n.nspname, 'function', p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')'
+        FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE p.prokind IN ('f', 'p')
+        UNION ALL
+        SELECT n.nspname, 'type', t.typname::text
+        FROM pg_type t JOIN pg_namespace n ON n.oid = t.typnamespace
+        LEFT JOIN pg_class c ON c.oid = t.typrelid
+        WHERE t.typtype IN ('c', 'd', 'e', 'r', 'm')
+          AND (t.typrelid = 0 OR c.relkind = 'c')
+        ORDER BY 1, 2, 3";
+    let rows = match client.query(query, &[]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load database structure: {}", e)),
+    };
+    
+    let mut objects: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for schema in list_schemas(connection_id, include_system).await? {
+        objects.insert(schema, BTreeMap::new());
+    }
+    
+    for row in rows {
+        let schema: String = row.get(0);
+        // Schemas filtered out by list_schemas are skipped
+        if let Some(kinds) = objects.get_mut(&schema) {
+            kinds.entry(row.get(1)).or_default().push(row.get(2));
+        }
+    }
+    
+    let schemas = objects.into_iter().map(|(schema, mut kinds)| {
+        let groups = SCHEMA_GROUPS.iter().map(|(kind, label)| {
+            let items = kinds.remove(*kind).unwrap_or_default().into_iter().map(|name| DatabaseItem {
+                name,
+                item_type: kind.to_string(),
+                children: None,
+            }).collect();
+            
+            DatabaseItem {
+                name: label.to_string(),
+                item_type: "group".to_string(),
+                children: Some(items),
+            }
+        }).collect();
+        
+        DatabaseItem {
+            name: schema,
+            item_type: "schema".to_string(),
+            children: Some(groups),
+        }
+    }).collect();
+    
+    Ok(DatabaseStructure {
+        items: vec![DatabaseItem {
+            name: database,
+            item_type: "database".to_string(),
+            children: Some(schemas),
+        }],
+    })
+}
+
+pub async fn describe_table(connection_id: &str, schema: Option<&str>, table: &str) -> Result<TableDescription, String> {
+    let client = get_client(connection_id)?;
+    let schema_name = schema.unwrap_or("public");
+    
+    let query = "SELECT c.oid, c.relkind::text, obj_description(c.oid, 'pg_class'), \
+                 CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END \
+                 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE n.nspname = $1 AND c.relname = $2";
+    let row = match client.query_opt(query, &[&schema_name, &table]).await {
+        Ok(Some(row)) => row,
+        Ok(None) => return Err(format!("Table {}.{} not found", schema_name, table)),
+        Err(e) => return Err(format!("Failed to describe table: {}", e)),
+    };
+    
+    let oid: Oid = row.get(0);
+    let kind = match row.get::<_, String>(1).as_str() {
+        "r" => "table",
+        "p" => "partitioned table",
+        "v" => "view",
+        "m" => "materialized view",
+        "f" => "foreign table",
+        _ => "other",
+    }.to_string();
+    
+    Ok(TableDescription {
+        schema: schema_name.to_string(),
+        name: table.to_string(),
+        kind,
+        comment: row.get(2),
+        partition_key: row.get(3),
+        columns: describe_columns(&client, oid).await?,
+        constraints: describe_constraints(&client, oid).await?,
+        indexes: describe_indexes(&client, oid).await?,
+        partitions: describe_partitions(&client, oid).await?,
+    })
+}
+
+async fn describe_columns(client: &Client, oid: Oid) -> Result<Vec<ColumnInfo>, String> {
+    // Generated columns keep their expression in pg_attrdef, so it's split out from the default
+    let query = "
+        SELECT a.attname::text, a.attnum, format_type(a.atttypid, a.atttypmod), NOT a.attnotnull,
+               CASE WHEN a.attgenerated = '' THEN pg_get_expr(d.adbin, d.adrelid) END,
+               CASE a.attidentity WHEN 'a' THEN 'ALWAYS' WHEN 'd' THEN 'BY DEFAULT' END,
+               CASE WHEN a.attgenerated <> '' THEN pg_get_expr(d.adbin, d.adrelid) END,
+               CASE WHEN a.attcollation <> t.typcollation THEN co.collname::text END,
+               col_description(a.attrelid, a.attnum)
+        FROM pg_attribute a
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        LEFT JOIN pg_collation co ON co.oid = a.attcollation
+        WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attnum";
+    let rows = match client.query(query, &[&oid]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load columns: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| ColumnInfo {
+        name: row.get(0),
+        position: row.get(1),
+        data_type: row.get(2),
+        nullable: row.get(3),
+        default: row.get(4),
+        identity: row.get(5),
+        generated: row.get(6),
+        collation: row.get(7),
+        comment: row.get(8),
+    }).collect())
+}
+
+async fn describe_constraints(client: &Client, oid: Oid) -> Result<Vec<ConstraintInfo>, String> {
+    let query = "
+        SELECT con.conname::text, con.contype::text, pg_get_constraintdef(con.oid, true),
+               ARRAY(SELECT a.attname::text FROM unnest(con.conkey) WITH ORDINALITY k(attnum, ord)
+                     JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+                     ORDER BY k.ord),
+               CASE WHEN con.confrelid <> 0 THEN con.confrelid::regclass::text END,
+               ARRAY(SELECT a.attname::text FROM unnest(con.confkey) WITH ORDINALITY k(attnum, ord)
+                     JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+                     ORDER BY k.ord)
+        FROM pg_constraint con
+        WHERE con.conrelid = $1
+        ORDER BY CASE con.contype WHEN 'p' THEN 0 WHEN 'u' THEN 1 WHEN 'f' THEN 2 ELSE 3 END, con.conname";
+    let rows = match client.query(query, &[&oid]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load constraints: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| {
+        let constraint_type = match row.get::<_, String>(1).as_str() {
+            "p" => "PRIMARY KEY",
+            "u" => "UNIQUE",
+            "c" => "CHECK",
+            "f" => "FOREIGN KEY",
+            "x" => "EXCLUSION",
+            "t" => "TRIGGER",
+            _ => "OTHER",
+        }.to_string();
+        
+        ConstraintInfo {
+            name: row.get(0),
+            constraint_type,
+            definition: row.get(2),
+            columns: row.get(3),
+            referenced_table: row.get(4),
+            referenced_columns: row.get(5),
+        }
+    }).collect())
+}
+
+async fn describe_indexes(client: &Client, oid: Oid) -> Result<Vec<IndexInfo>, String> {
+    let query = "
+        SELECT i.relname::text, pg_get_indexdef(ix.indexrelid), am.amname::text,
+               ix.indisunique, ix.indisprimary, ix.indisvalid
+        FROM pg_index ix
+        JOIN pg_class i ON i.oid = ix.indexrelid
+        JOIN pg_am am ON am.oid = i.relam
+        WHERE ix.indrelid = $1
+        ORDER BY ix.indisprimary DESC, i.relname";
+    let rows = match client.query(query, &[&oid]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load indexes: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| IndexInfo {
+        name: row.get(0),
+        definition: row.get(1),
+        method: row.get(2),
+        is_unique: row.get(3),
+        is_primary: row.get(4),
+        is_valid: row.get(5),
+    }).collect())
+}
+
+async fn describe_partitions(client: &Client, oid: Oid) -> Result<Vec<PartitionInfo>, String> {
+    let query = "
+        SELECT c.oid::regclass::text, pg_get_expr(c.relpartbound, c.oid)
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        WHERE i.inhparent = $1 AND c.relispartition
+        ORDER BY 1";
+    let rows = match client.query(query, &[&oid]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load partitions: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| PartitionInfo {
+        name: row.get(0),
+        bound: row.get(1),
+    }).collect())
+}
+
+pub async fn listen(connection_id: &str, channel: &str) -> Result<(), String> {
+    let client = get_client(connection_id)?;
+    
+    match client.batch_execute(&format!("LISTEN {}", quote_ident(channel))).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to listen on channel {}: {}", channel, e)),
+    }
+}
+
+pub async fn unlisten(connection_id: &str, channel: Option<&str>) -> Result<(), String> {
+    let client = get_client(connection_id)?;
+    
+    // Without a channel stop listening everywhere
+    let statement = match channel {
+        Some(channel) => format!("UNLISTEN {}", quote_ident(channel)),
+        None => "UNLISTEN *".to_string(),
+    };
+    
+    match client.batch_execute(&statement).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to unlisten: {}", e)),
+    }
+}
+
+pub async fn notify(connection_id: &str, channel: &str, payload: Option<&str>) -> Result<(), String> {
+    let client = get_client(connection_id)?;
+    
+    // pg_notify takes the channel as a value, so nothing needs quoting here
+    match client.execute("SELECT pg_notify($1, $2)", &[&channel, &payload.unwrap_or("")]).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to notify channel {}: {}", channel, e)),
+    }
+}
+
+pub async fn list_channels(connection_id: &str) -> Result<Vec<String>, String> {
+    let client = get_client(connection_id)?;
+    
+    match client.query("SELECT pg_listening_channels()", &[]).await {
+        Ok(rows) => Ok(rows.iter().map(|row| row.get(0)).collect()),
+        Err(e) => Err(format!("Failed to list channels: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn quote_ident_always_quotes_and_doubles_quotes() {
+        assert_eq!(quote_ident("users"), "\"users\"");
+        assert_eq!(quote_ident("Order Items"), "\"Order Items\"");
+        assert_eq!(quote_ident("a\"; DROP TABLE x; --"), "\"a\"\"; DROP TABLE x; --\"");
+    }
+    
+    #[test]
+    fn quote_literal_escapes_quotes_and_backslashes() {
+        assert_eq!(quote_literal("plain"), "'plain'");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal("C:\\temp"), "E'C:\\\\temp'");
+        assert_eq!(quote_literal("a\\'; --"), "E'a\\\\''; --'");
+    }
+}
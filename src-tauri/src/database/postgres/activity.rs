@@ -0,0 +1,204 @@
+use crate::database::POSTGRES_MONITORS;
+use crate::database::models::{ActivitySnapshot, LockNode, SessionInfo};
+use super::get_client;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio_postgres::Client;
+
+// Event emitted with each snapshot taken by a running activity monitor
+pub const ACTIVITY_EVENT: &str = "postgres-activity";
+
+// Polling faster than this would mostly measure the monitor itself
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub async fn get_sessions(connection_id: &str, include_idle: bool) -> Result<Vec<SessionInfo>, String> {
+    let client = get_client(connection_id)?;
+    load_sessions(&client, include_idle).await
+}
+
+pub async fn get_lock_tree(connection_id: &str) -> Result<Vec<LockNode>, String> {
+    let client = get_client(connection_id)?;
+    load_lock_tree(&client).await
+}
+
+pub async fn cancel_backend(connection_id: &str, pid: i32) -> Result<bool, String> {
+    let client = get_client(connection_id)?;
+    
+    match client.query_one("SELECT pg_cancel_backend($1)", &[&pid]).await {
+        Ok(row) => Ok(row.get(0)),
+        Err(e) => Err(format!("Failed to cancel backend {}: {}", pid, e)),
+    }
+}
+
+pub async fn terminate_backend(connection_id: &str, pid: i32) -> Result<bool, String> {
+    let client = get_client(connection_id)?;
+    
+    match client.query_one("SELECT pg_terminate_backend($1)", &[&pid]).await {
+        Ok(row) => Ok(row.get(0)),
+        Err(e) => Err(format!("Failed to terminate backend {}: {}", pid, e)),
+    }
+}
+
+pub fn start_monitor(app: AppHandle, connection_id: &str, interval_ms: u64, include_idle: bool) -> Result<(), String> {
+    let client = get_client(connection_id)?;
+    let interval = Duration::from_millis(interval_ms).max(MIN_POLL_INTERVAL);
+    let monitored_id = connection_id.to_string();
+    
+    let handle = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            
+            let result = match load_sessions(&client, include_idle).await {
+                Ok(sessions) => load_lock_tree(&client).await.map(|locks| (sessions, locks)),
+                Err(e) => Err(e),
+            };
+            let snapshot = match result {
+                Ok((sessions, locks)) => ActivitySnapshot {
+                    connection_id: monitored_id.clone(),
+                    sessions,
+                    locks,
+                    error: None,
+                },
+                Err(e) => ActivitySnapshot {
+                    connection_id: monitored_id.clone(),
+                    sessions: Vec::new(),
+                    locks: Vec::new(),
+                    error: Some(e),
+                },
+            };
+            
+            // Keep polling after errors so a restarted server is picked up again
+            if let Err(e) = app.emit(ACTIVITY_EVENT, snapshot) {
+                eprintln!("Failed to emit PostgreSQL activity: {}", e);
+            }
+        }
+    });
+    
+    // Replacing a running monitor stops the old one
+    let mut monitors = POSTGRES_MONITORS.lock().map_err(|e| e.to_string())?;
+    if let Some(previous) = monitors.insert(connection_id.to_string(), handle) {
+        previous.abort();
+    }
+    
+    Ok(())
+}
+
+pub fn stop_monitor(connection_id: &str) -> Result<(), String> {
+    let mut monitors = POSTGRES_MONITORS.lock().map_err(|e| e.to_string())?;
+    
+    match monitors.remove(connection_id) {
+        Some(handle) => {
+            handle.abort();
+            Ok(())
+        },
+        None => Err(format!("Connection with ID {} is not being monitored", connection_id)),
+    }
+}
+
+async fn load_sessions(client: &Client, include_idle: bool) -> Result<Vec<SessionInfo>, String> {
+    let query = "
+        SELECT pid, datname, usename, application_name, client_addr::text, state,
+               wait_event_type, wait_event, query,
+               (EXTRACT(EPOCH FROM clock_timestamp() - query_start) * 1000)::float8,
+               (EXTRACT(EPOCH FROM clock_timestamp() - xact_start) * 1000)::float8,
+               pg_blocking_pids(pid)
+        FROM pg_stat_activity
+        WHERE backend_type = 'client backend' AND pid <> pg_backend_pid()
+          AND ($1 OR state IS DISTINCT FROM 'idle')
+        ORDER BY query_start NULLS LAST";
+    let rows = match client.query(query, &[&include_idle]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load sessions: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| SessionInfo {
+        pid: row.get(0),
+        database: row.get(1),
+        user: row.get(2),
+        application: row.get(3),
+        client_address: row.get(4),
+        state: row.get(5),
+        wait_event_type: row.get(6),
+        wait_event: row.get(7),
+        query: row.get(8),
+        query_duration_ms: row.get(9),
+        transaction_duration_ms: row.get(10),
+        blocking_pids: row.get(11),
+    }).collect())
+}
+
+async fn load_lock_tree(client: &Client) -> Result<Vec<LockNode>, String> {
+    // Every backend that is blocked or blocking others, with the lock it is waiting for if any
+    let query = "
+        WITH blocked AS (
+            SELECT pid, pg_blocking_pids(pid) AS blockers FROM pg_stat_activity
+            WHERE cardinality(pg_blocking_pids(pid)) > 0
+        ),
+        involved AS (
+            SELECT pid FROM blocked
+            UNION
+            SELECT unnest(blockers) FROM blocked
+        )
+        SELECT a.pid, a.usename, a.state, a.query,
+               (EXTRACT(EPOCH FROM clock_timestamp() - a.query_start) * 1000)::float8,
+               COALESCE(b.blockers, '{}'),
+               l.locktype, l.mode, l.relation::regclass::text
+        FROM involved i
+        JOIN pg_stat_activity a ON a.pid = i.pid
+        LEFT JOIN blocked b ON b.pid = a.pid
+        LEFT JOIN LATERAL (
+            SELECT locktype, mode, relation FROM pg_locks
+            WHERE pid = a.pid AND NOT granted
+            LIMIT 1
+        ) l ON true
+        ORDER BY a.pid";
+    let rows = match client.query(query, &[]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load locks: {}", e)),
+    };
+    
+    let mut nodes: HashMap<i32, LockNode> = rows.iter().map(|row| {
+        let pid: i32 = row.get(0);
+        (pid, LockNode {
+            pid,
+            user: row.get(1),
+            state: row.get(2),
+            query: row.get(3),
+            duration_ms: row.get(4),
+            blocked_by: row.get(5),
+            lock_type: row.get(6),
+            mode: row.get(7),
+            relation: row.get(8),
+            children: Vec::new(),
+        })
+    }).collect();
+    
+    // Roots are backends not waiting on anyone listed, e.g. blockers or waiters on a prepared transaction
+    let mut roots: Vec<i32> = nodes.values()
+        .filter(|n| !n.blocked_by.iter().any(|blocker| nodes.contains_key(blocker)))
+        .map(|n| n.pid)
+        .collect();
+    roots.sort_unstable();
+    
+    let mut tree: Vec<LockNode> = roots.into_iter().filter_map(|pid| build_lock_node(pid, &mut nodes)).collect();
+    
+    // Whatever is left waits in a cycle, so start from its lowest pid
+    while let Some(pid) = nodes.keys().min().copied() {
+        tree.extend(build_lock_node(pid, &mut nodes));
+    }
+    
+    Ok(tree)
+}
+
+fn build_lock_node(pid: i32, nodes: &mut HashMap<i32, LockNode>) -> Option<LockNode> {
+    // Removing the node first means a backend blocked by several others appears only once
+    let mut node = nodes.remove(&pid)?;
+    
+    let mut waiters: Vec<i32> = nodes.values().filter(|n| n.blocked_by.contains(&pid)).map(|n| n.pid).collect();
+    waiters.sort_unstable();
+    node.children = waiters.into_iter().filter_map(|waiter| build_lock_node(waiter, nodes)).collect();
+    
+    Some(node)
+}
@@ -0,0 +1,190 @@
+use crate::database::models::{CopyOptions, CopyProgress, CopyResult};
+use super::{get_client, quote_ident, quote_literal};
+use bytes::Bytes;
+use futures::{pin_mut, SinkExt, StreamExt};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+// Event emitted while a COPY transfers data to or from a file
+pub const COPY_PROGRESS_EVENT: &str = "postgres-copy-progress";
+
+// Size of each chunk read from the import file
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+// Minimum time between progress events so large files don't flood the frontend
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+pub async fn copy_in(
+    app: AppHandle,
+    connection_id: &str,
+    schema: Option<&str>,
+    table: &str,
+    path: &str,
+    options: &CopyOptions,
+) -> Result<CopyResult, String> {
+    let client = get_client(connection_id)?;
+    
+    let target = format!("{}.{}", quote_ident(schema.unwrap_or("public")), quote_ident(table));
+    let columns = match &options.columns {
+        Some(columns) if !columns.is_empty() => {
+            format!(" ({})", columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", "))
+        },
+        _ => String::new(),
+    };
+    let statement = format!("COPY {}{} FROM STDIN{}", target, columns, copy_options(options)?);
+    
+    let mut file = File::open(path).await
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let total_bytes = file.metadata().await.ok().map(|m| m.len());
+    
+    let sink = client.copy_in::<_, Bytes>(&statement).await
+        .map_err(|e| format!("Failed to start COPY: {}", e))?;
+    pin_mut!(sink);
+    
+    let mut progress = Progress::new(app, connection_id, path, "in", total_bytes);
+    let mut buffer = vec![0u8; COPY_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        
+        sink.send(Bytes::copy_from_slice(&buffer[..read])).await
+            .map_err(|e| format!("Failed to send COPY data: {}", e))?;
+        progress.advance(read as u64);
+    }
+    
+    let rows = sink.finish().await
+        .map_err(|e| format!("Failed to finish COPY: {}", e))?;
+    progress.finish();
+    
+    Ok(CopyResult {
+        rows: Some(rows),
+        bytes: progress.bytes,
+    })
+}
+
+pub async fn copy_out(
+    app: AppHandle,
+    connection_id: &str,
+    query: &str,
+    path: &str,
+    options: &CopyOptions,
+) -> Result<CopyResult, String> {
+    let client = get_client(connection_id)?;
+    
+    let statement = format!(
+        "COPY ({}) TO STDOUT{}",
+        query.trim().trim_end_matches(';'),
+        copy_options(options)?
+    );
+    
+    let stream = client.copy_out(&statement).await
+        .map_err(|e| format!("Failed to start COPY: {}", e))?;
+    pin_mut!(stream);
+    
+    let mut file = File::create(path).await
+        .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    
+    let mut progress = Progress::new(app, connection_id, path, "out", None);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to receive COPY data: {}", e))?;
+        file.write_all(&chunk).await
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        progress.advance(chunk.len() as u64);
+    }
+    
+    file.flush().await
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    progress.finish();
+    
+    // The server doesn't report a row count for COPY TO STDOUT
+    Ok(CopyResult {
+        rows: None,
+        bytes: progress.bytes,
+    })
+}
+
+fn copy_options(options: &CopyOptions) -> Result<String, String> {
+    let mut parts = Vec::new();
+    
+    let format = options.format.as_deref().unwrap_or("csv").to_lowercase();
+    match format.as_str() {
+        "csv" | "text" | "binary" => parts.push(format!("FORMAT {}", format)),
+        _ => return Err(format!("Unsupported COPY format: {}", format)),
+    }
+    
+    if let Some(header) = options.header {
+        parts.push(format!("HEADER {}", header));
+    }
+    if let Some(delimiter) = &options.delimiter {
+        parts.push(format!("DELIMITER {}", quote_literal(delimiter)));
+    }
+    if let Some(quote) = &options.quote {
+        parts.push(format!("QUOTE {}", quote_literal(quote)));
+    }
+    if let Some(escape) = &options.escape {
+        parts.push(format!("ESCAPE {}", quote_literal(escape)));
+    }
+    if let Some(null) = &options.null {
+        parts.push(format!("NULL {}", quote_literal(null)));
+    }
+    if let Some(encoding) = &options.encoding {
+        parts.push(format!("ENCODING {}", quote_literal(encoding)));
+    }
+    
+    Ok(format!(" WITH ({})", parts.join(", ")))
+}
+
+struct Progress {
+    app: AppHandle,
+    connection_id: String,
+    path: String,
+    direction: &'static str,
+    total_bytes: Option<u64>,
+    bytes: u64,
+    last_emit: Instant,
+}
+
+impl Progress {
+    fn new(app: AppHandle, connection_id: &str, path: &str, direction: &'static str, total_bytes: Option<u64>) -> Self {
+        Progress {
+            app,
+            connection_id: connection_id.to_string(),
+            path: path.to_string(),
+            direction,
+            total_bytes,
+            bytes: 0,
+            last_emit: Instant::now(),
+        }
+    }
+    
+    fn advance(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            self.emit(false);
+            self.last_emit = Instant::now();
+        }
+    }
+    
+    fn finish(&self) {
+        self.emit(true);
+    }
+    
+    fn emit(&self, done: bool) {
+        let payload = CopyProgress {
+            connection_id: self.connection_id.clone(),
+            path: self.path.clone(),
+            direction: self.direction.to_string(),
+            bytes: self.bytes,
+            total_bytes: self.total_bytes,
+            done,
+        };
+        if let Err(e) = self.app.emit(COPY_PROGRESS_EVENT, payload) {
+            eprintln!("Failed to emit COPY progress: {}", e);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use crate::database::models::DatabaseObject;
+use super::get_client;
+use tokio_postgres::Client;
+use tokio_postgres::types::Oid;
+
+// Relation kinds accepted for each object kind
+pub(super) const RELATION_KINDS: &[(&str, &str)] = &[
+    ("table", "rp"),
+    ("view", "v"),
+    ("materialized_view", "m"),
+    ("index", "iI"),
+    ("sequence", "S"),
+];
+
+pub async fn get_ddl(connection_id: &str, object: &DatabaseObject) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let schema = object.schema.as_deref().unwrap_or("public");
+    
+    match object.kind.as_str() {
+        "function" | "procedure" => function_ddl(&client, schema, &object.name).await,
+        "type" => type_ddl(&client, schema, &object.name).await,
+        kind => {
+            let relkinds = match RELATION_KINDS.iter().find(|(k, _)| *k == kind) {
+                Some((_, relkinds)) => *relkinds,
+                None => return Err(format!("Unsupported object kind: {}", kind)),
+            };
+            
+            let query = "SELECT c.oid FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                         WHERE n.nspname = $1 AND c.relname = $2 AND strpos($3, c.relkind::text) > 0";
+            let oid: Oid = match client.query_opt(query, &[&schema, &object.name, &relkinds]).await {
+                Ok(Some(row)) => row.get(0),
+                Ok(None) => return Err(format!("{} {}.{} not found", kind, schema, object.name)),
+                Err(e) => return Err(format!("Failed to look up {}: {}", kind, e)),
+            };
+            
+            match kind {
+                "table" => table_ddl(&client, oid).await,
+                "view" | "materialized_view" => view_ddl(&client, oid).await,
+                "index" => index_ddl(&client, oid).await,
+                _ => sequence_ddl(&client, oid).await,
+            }
+        },
+    }
+}
+
+async fn table_ddl(client: &Client, oid: Oid) -> Result<String, String> {
+    let query = "
+        SELECT c.oid::regclass::text, c.relispartition, pg_get_partkeydef(c.oid),
+               (SELECT i.inhparent::regclass::text FROM pg_inherits i WHERE i.inhrelid = c.oid LIMIT 1),
+               pg_get_expr(c.relpartbound, c.oid)
+        FROM pg_class c WHERE c.oid = $1";
+    let row = client.query_one(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load table: {}", e))?;
+    
+    let name: String = row.get(0);
+    let is_partition: bool = row.get(1);
+    let partition_key: Option<String> = row.get(2);
+    
+    let mut ddl = if is_partition {
+        // Partitions inherit their columns and constraints from the parent
+        let parent: String = row.get(3);
+        let bound: String = row.get(4);
+        format!("CREATE TABLE {} PARTITION OF {}\n    {}", name, parent, bound)
+    } else {
+        let mut lines = table_columns(client, oid).await?;
+        
+        let query = "SELECT quote_ident(conname), pg_get_constraintdef(oid, true) FROM pg_constraint \
+                     WHERE conrelid = $1 AND contype <> 't' \
+                     ORDER BY CASE contype WHEN 'p' THEN 0 WHEN 'u' THEN 1 WHEN 'c' THEN 2 ELSE 3 END, conname";
+        let rows = client.query(query, &[&oid]).await
+            .map_err(|e| format!("Failed to load constraints: {}", e))?;
+        for row in rows {
+            lines.push(format!("CONSTRAINT {} {}", row.get::<_, String>(0), row.get::<_, String>(1)));
+        }
+        
+        format!("CREATE TABLE {} (\n    {}\n)", name, lines.join(",\n    "))
+    };
+    
+    if let Some(key) = partition_key {
+        ddl.push_str(&format!("\nPARTITION BY {}", key));
+    }
+    ddl.push_str(";\n");
+    
+    // Indexes backing constraints are created by the constraints themselves
+    let query = "SELECT pg_get_indexdef(ix.indexrelid) FROM pg_index ix JOIN pg_class i ON i.oid = ix.indexrelid \
+                 WHERE ix.indrelid = $1 AND NOT EXISTS (SELECT 1 FROM pg_constraint con WHERE con.conindid = ix.indexrelid) \
+                 ORDER BY i.relname";
+    let rows = client.query(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load indexes: {}", e))?;
+    if !rows.is_empty() {
+        ddl.push('\n');
+        for row in rows {
+            ddl.push_str(&format!("{};\n", row.get::<_, String>(0)));
+        }
+    }
+    
+    ddl.push_str(&relation_comments(client, oid, "TABLE", &name).await?);
+    ddl.push_str(&owner_and_grants(client, oid, "pg_class", "relacl", "relowner", "TABLE", &name).await?);
+    
+    Ok(ddl)
+}
+
+async fn table_columns(client: &Client, oid: Oid) -> Result<Vec<String>, String> {
+    let query = "
+        SELECT quote_ident(a.attname), format_type(a.atttypid, a.atttypmod),
+               CASE WHEN a.attcollation <> t.typcollation THEN quote_ident(co.collname) END,
+               a.attidentity::text, a.attgenerated::text, pg_get_expr(d.adbin, d.adrelid), a.attnotnull
+        FROM pg_attribute a
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        LEFT JOIN pg_collation co ON co.oid = a.attcollation
+        WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attnum";
+    let rows = client.query(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load columns: {}", e))?;
+    
+    Ok(rows.iter().map(|row| {
+        let mut column = format!("{} {}", row.get::<_, String>(0), row.get::<_, String>(1));
+        
+        if let Some(collation) = row.get::<_, Option<String>>(2) {
+            column.push_str(&format!(" COLLATE {}", collation));
+        }
+        
+        let expression: Option<String> = row.get(5);
+        match (row.get::<_, String>(3).as_str(), row.get::<_, String>(4).as_str()) {
+            ("a", _) => column.push_str(" GENERATED ALWAYS AS IDENTITY"),
+            ("d", _) => column.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+            (_, "s") => column.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expression.unwrap_or_default())),
+            _ => if let Some(default) = expression {
+                column.push_str(&format!(" DEFAULT {}", default));
+            },
+        }
+        
+        if row.get::<_, bool>(6) {
+            column.push_str(" NOT NULL");
+        }
+        
+        column
+    }).collect())
+}
+
+async fn view_ddl(client: &Client, oid: Oid) -> Result<String, String> {
+    let query = "SELECT c.oid::regclass::text, c.relkind::text, pg_get_viewdef(c.oid, true) FROM pg_class c WHERE c.oid = $1";
+    let row = client.query_one(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load view: {}", e))?;
+    
+    let name: String = row.get(0);
+    let materialized = row.get::<_, String>(1) == "m";
+    let definition: String = row.get(2);
+    
+    let (create, label) = if materialized {
+        ("CREATE MATERIALIZED VIEW", "MATERIALIZED VIEW")
+    } else {
+        ("CREATE OR REPLACE VIEW", "VIEW")
+    };
+    let mut ddl = format!("{} {} AS\n{}\n", create, name, definition.trim_end());
+    
+    if materialized {
+        let query = "SELECT pg_get_indexdef(indexrelid) FROM pg_index WHERE indrelid = $1 ORDER BY indexrelid";
+        let rows = client.query(query, &[&oid]).await
+            .map_err(|e| format!("Failed to load indexes: {}", e))?;
+        if !rows.is_empty() {
+            ddl.push('\n');
+            for row in rows {
+                ddl.push_str(&format!("{};\n", row.get::<_, String>(0)));
+            }
+        }
+    }
+    
+    ddl.push_str(&relation_comments(client, oid, label, &name).await?);
+    ddl.push_str(&owner_and_grants(client, oid, "pg_class", "relacl", "relowner", "TABLE", &name).await?);
+    
+    Ok(ddl)
+}
+
+async fn index_ddl(client: &Client, oid: Oid) -> Result<String, String> {
+    let query = "SELECT c.oid::regclass::text, pg_get_indexdef(c.oid) FROM pg_class c WHERE c.oid = $1";
+    let row = client.query_one(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load index: {}", e))?;
+    
+    let name: String = row.get(0);
+    let mut ddl = format!("{};\n", row.get::<_, String>(1));
+    ddl.push_str(&comment_statement(client, oid, "pg_class", "INDEX", &name).await?);
+    
+    Ok(ddl)
+}
+
+async fn sequence_ddl(client: &Client, oid: Oid) -> Result<String, String> {
+    let query = "
+        SELECT c.oid::regclass::text, format_type(s.seqtypid, NULL), s.seqincrement, s.seqmin, s.seqmax,
+               s.seqstart, s.seqcache, s.seqcycle,
+               (SELECT d.refobjid::regclass::text || '.' || quote_ident(a.attname)
+                FROM pg_depend d
+                JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+                WHERE d.objid = c.oid AND d.classid = 'pg_class'::regclass AND d.deptype = 'a'
+                LIMIT 1)
+        FROM pg_class c JOIN pg_sequence s ON s.seqrelid = c.oid
+        WHERE c.oid = $1";
+    let row = client.query_one(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load sequence: {}", e))?;
+    
+    let name: String = row.get(0);
+    let mut ddl = format!(
+        "CREATE SEQUENCE {}\n    AS {}\n    INCREMENT BY {}\n    MINVALUE {}\n    MAXVALUE {}\n    START WITH {}\n    CACHE {}{};\n",
+        name,
+        row.get::<_, String>(1),
+        row.get::<_, i64>(2),
+        row.get::<_, i64>(3),
+        row.get::<_, i64>(4),
+        row.get::<_, i64>(5),
+        row.get::<_, i64>(6),
+        if row.get::<_, bool>(7) { "\n    CYCLE" } else { "" },
+    );
+    
+    if let Some(owned_by) = row.get::<_, Option<String>>(8) {
+        ddl.push_str(&format!("\nALTER SEQUENCE {} OWNED BY {};\n", name, owned_by));
+    }
+    
+    ddl.push_str(&comment_statement(client, oid, "pg_class", "SEQUENCE", &name).await?);
+    ddl.push_str(&owner_and_grants(client, oid, "pg_class", "relacl", "relowner", "SEQUENCE", &name).await?);
+    
+    Ok(ddl)
+}
+
+async fn function_ddl(client: &Client, schema: &str, name: &str) -> Result<String, String> {
+    // Functions can be given by name (all overloads) or by signature as listed in the explorer
+    let query = "
+        SELECT p.oid, p.oid::regprocedure::text, p.prokind::text, pg_get_functiondef(p.oid)
+        FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = $1 AND p.prokind IN ('f', 'p')
+          AND (p.proname = $2 OR p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')' = $2)
+        ORDER BY p.oid";
+    let rows = client.query(query, &[&schema, &name]).await
+        .map_err(|e| format!("Failed to load function: {}", e))?;
+    
+    if rows.is_empty() {
+        return Err(format!("function {}.{} not found", schema, name));
+    }
+    
+    let mut statements = Vec::new();
+    for row in rows {
+        let oid: Oid = row.get(0);
+        let signature: String = row.get(1);
+        let label = if row.get::<_, String>(2) == "p" { "PROCEDURE" } else { "FUNCTION" };
+        
+        let mut ddl = format!("{};\n", row.get::<_, String>(3).trim_end());
+        ddl.push_str(&comment_statement(client, oid, "pg_proc", label, &signature).await?);
+        ddl.push_str(&owner_and_grants(client, oid, "pg_proc", "proacl", "proowner", label, &signature).await?);
+        statements.push(ddl);
+    }
+    
+    Ok(statements.join("\n"))
+}
+
+async fn type_ddl(client: &Client, schema: &str, name: &str) -> Result<String, String> {
+    let query = "
+        SELECT t.oid, t.oid::regtype::text, t.typtype::text, t.typrelid,
+               format_type(t.typbasetype, t.typtypmod), t.typnotnull, t.typdefault,
+               CASE WHEN t.typcollation <> bt.typcollation THEN quote_ident(co.collname) END
+        FROM pg_type t
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        LEFT JOIN pg_type bt ON bt.oid = t.typbasetype
+        LEFT JOIN pg_collation co ON co.oid = t.typcollation
+        WHERE n.nspname = $1 AND t.typname = $2";
+    let row = match client.query_opt(query, &[&schema, &name]).await {
+        Ok(Some(row)) => row,
+        Ok(None) => return Err(format!("type {}.{} not found", schema, name)),
+        Err(e) => return Err(format!("Failed to load type: {}", e)),
+    };
+    
+    let oid: Oid = row.get(0);
+    let type_name: String = row.get(1);
+    
+    let typtype: String = row.get(2);
+    let label = if typtype == "d" { "DOMAIN" } else { "TYPE" };
+    
+    let mut ddl = match typtype.as_str() {
+        "e" => {
+            let query = "SELECT quote_literal(enumlabel) FROM pg_enum WHERE enumtypid = $1 ORDER BY enumsortorder";
+            let labels: Vec<String> = client.query(query, &[&oid]).await
+                .map_err(|e| format!("Failed to load enum labels: {}", e))?
+                .iter().map(|row| row.get(0)).collect();
+            format!("CREATE TYPE {} AS ENUM (\n    {}\n);\n", type_name, labels.join(",\n    "))
+        },
+        "c" => {
+            let relid: Oid = row.get(3);
+            let query = "SELECT quote_ident(attname) || ' ' || format_type(atttypid, atttypmod) FROM pg_attribute \
+                         WHERE attrelid = $1 AND attnum > 0 AND NOT attisdropped ORDER BY attnum";
+            let attributes: Vec<String> = client.query(query, &[&relid]).await
+                .map_err(|e| format!("Failed to load type attributes: {}", e))?
+                .iter().map(|row| row.get(0)).collect();
+            format!("CREATE TYPE {} AS (\n    {}\n);\n", type_name, attributes.join(",\n    "))
+        },
+        "d" => {
+            let mut ddl = format!("CREATE DOMAIN {} AS {}", type_name, row.get::<_, String>(4));
+            if let Some(collation) = row.get::<_, Option<String>>(7) {
+                ddl.push_str(&format!(" COLLATE {}", collation));
+            }
+            if let Some(default) = row.get::<_, Option<String>>(6) {
+                ddl.push_str(&format!(" DEFAULT {}", default));
+            }
+            if row.get::<_, bool>(5) {
+                ddl.push_str(" NOT NULL");
+            }
+            
+            let query = "SELECT quote_ident(conname), pg_get_constraintdef(oid, true) FROM pg_constraint \
+                         WHERE contypid = $1 ORDER BY conname";
+            let rows = client.query(query, &[&oid]).await
+                .map_err(|e| format!("Failed to load domain constraints: {}", e))?;
+            for row in rows {
+                ddl.push_str(&format!("\n    CONSTRAINT {} {}", row.get::<_, String>(0), row.get::<_, String>(1)));
+            }
+            ddl.push_str(";\n");
+            ddl
+        },
+        "r" => {
+            let query = "SELECT format_type(rngsubtype, NULL) FROM pg_range WHERE rngtypid = $1";
+            let subtype: String = client.query_one(query, &[&oid]).await
+                .map_err(|e| format!("Failed to load range type: {}", e))?
+                .get(0);
+            format!("CREATE TYPE {} AS RANGE (\n    SUBTYPE = {}\n);\n", type_name, subtype)
+        },
+        _ => return Err(format!("DDL generation is not supported for type {}", type_name)),
+    };
+    
+    ddl.push_str(&comment_statement(client, oid, "pg_type", label, &type_name).await?);
+    ddl.push_str(&owner_and_grants(client, oid, "pg_type", "typacl", "typowner", label, &type_name).await?);
+    
+    Ok(ddl)
+}
+
+// COMMENT ON statements for a relation and its columns
+async fn relation_comments(client: &Client, oid: Oid, label: &str, name: &str) -> Result<String, String> {
+    let mut ddl = comment_statement(client, oid, "pg_class", label, name).await?;
+    
+    let query = "SELECT quote_ident(a.attname), quote_literal(d.description) FROM pg_description d \
+                 JOIN pg_attribute a ON a.attrelid = d.objoid AND a.attnum = d.objsubid \
+                 WHERE d.objoid = $1 AND d.classoid = 'pg_class'::regclass AND d.objsubid > 0 \
+                 ORDER BY a.attnum";
+    let rows = client.query(query, &[&oid]).await
+        .map_err(|e| format!("Failed to load column comments: {}", e))?;
+    
+    for row in rows {
+        ddl.push_str(&format!(
+            "COMMENT ON COLUMN {}.{} IS {};\n",
+            name,
+            row.get::<_, String>(0),
+            row.get::<_, String>(1)
+        ));
+    }
+    
+    Ok(ddl)
+}
+
+async fn comment_statement(client: &Client, oid: Oid, catalog: &str, label: &str, name: &str) -> Result<String, String> {
+    let query = "SELECT quote_literal(obj_description($1, $2))";
+    let comment: Option<String> = client.query_one(query, &[&oid, &catalog]).await
+        .map_err(|e| format!("Failed to load comment: {}", e))?
+        .get(0);
+    
+    Ok(match comment {
+        Some(comment) => format!("\nCOMMENT ON {} {} IS {};\n", label, name, comment),
+        None => String::new(),
+    })
+}
+
+// ALTER ... OWNER TO and GRANT statements rebuilt from the object's ACL
+async fn owner_and_grants(
+    client: &Client,
+    oid: Oid,
+    catalog: &str,
+    acl_column: &str,
+    owner_column: &str,
+    label: &str,
+    name: &str,
+) -> Result<String, String> {
+    // Catalog and column names come from the callers above, never from user input
+    let query = format!(
+        "SELECT quote_ident(pg_get_userbyid(o.{owner})), \
+                CASE WHEN a.grantee = 0 THEN 'PUBLIC' ELSE quote_ident(pg_get_userbyid(a.grantee)) END, \
+                string_agg(a.privilege_type, ', ' ORDER BY a.privilege_type), a.is_grantable \
+         FROM {catalog} o LEFT JOIN LATERAL aclexplode(o.{acl}) a ON a.grantee <> o.{owner} \
+         WHERE o.oid = $1 \
+         GROUP BY o.{owner}, a.grantee, a.is_grantable \
+         ORDER BY 2",
+        owner = owner_column,
+        catalog = catalog,
+        acl = acl_column,
+    );
+    let rows = client.query(&query, &[&oid]).await
+        .map_err(|e| format!("Failed to load privileges: {}", e))?;
+    
+    let mut ddl = String::new();
+    
+    // GRANT uses TABLE for views and materialized views, but ALTER needs the real kind
+    let alter_label = if label == "TABLE" || label == "VIEW" || label == "MATERIALIZED VIEW" {
+        relation_label(client, oid, catalog).await?
+    } else {
+        label.to_string()
+    };
+    let grant_label = if catalog == "pg_class" && label != "SEQUENCE" { "TABLE" } else { label };
+    
+    if let Some(row) = rows.first() {
+        ddl.push_str(&format!("\nALTER {} {} OWNER TO {};\n", alter_label, name, row.get::<_, String>(0)));
+    }
+    
+    for row in &rows {
+        let grantee: Option<String> = row.get(1);
+        let privileges: Option<String> = row.get(2);
+        if let (Some(grantee), Some(privileges)) = (grantee, privileges) {
+            let grant_option = if row.get::<_, Option<bool>>(3).unwrap_or(false) { " WITH GRANT OPTION" } else { "" };
+            ddl.push_str(&format!("GRANT {} ON {} {} TO {}{};\n", privileges, grant_label, name, grantee, grant_option));
+        }
+    }
+    
+    Ok(ddl)
+}
+
+async fn relation_label(client: &Client, oid: Oid, catalog: &str) -> Result<String, String> {
+    if catalog != "pg_class" {
+        return Ok("TABLE".to_string());
+    }
+    
+    let relkind: String = client.query_one("SELECT relkind::text FROM pg_class WHERE oid = $1", &[&oid]).await
+        .map_err(|e| format!("Failed to load relation: {}", e))?
+        .get(0);
+    
+    Ok(match relkind.as_str() {
+        "v" => "VIEW",
+        "m" => "MATERIALIZED VIEW",
+        "S" => "SEQUENCE",
+        _ => "TABLE",
+    }.to_string())
+}
@@ -0,0 +1,225 @@
+use crate::database::models::{PlanNode, QueryPlan};
+use super::{get_client, open_session, quote_ident};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use tokio_postgres::Client;
+use tokio_postgres::types::{FromSql, Type};
+
+// Sequential scans reading at least this many rows are flagged as hot spots
+const SEQ_SCAN_HOT_ROWS: f64 = 10_000.0;
+
+// Estimated and actual row counts differing by this factor are flagged as misestimates
+const MISESTIMATE_FACTOR: f64 = 10.0;
+
+// Plan properties summarised into a node's detail: (JSON key, label)
+const DETAIL_KEYS: &[(&str, &str)] = &[
+    ("Join Type", "Join"),
+    ("Strategy", "Strategy"),
+    ("Index Name", "Index"),
+    ("Index Cond", "Index Cond"),
+    ("Hash Cond", "Hash Cond"),
+    ("Merge Cond", "Merge Cond"),
+    ("Recheck Cond", "Recheck Cond"),
+    ("Join Filter", "Join Filter"),
+    ("Filter", "Filter"),
+    ("Sort Key", "Sort Key"),
+    ("Group Key", "Group Key"),
+    ("Sort Method", "Sort Method"),
+];
+
+// EXPLAIN (FORMAT JSON) returns a json column, whose binary form is the JSON text
+struct JsonText(String);
+
+impl<'a> FromSql<'a> for JsonText {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(JsonText(String::from_utf8(raw.to_vec())?))
+    }
+    
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::JSON
+    }
+}
+
+pub async fn explain_query(connection_id: &str, query: &str, analyze: bool) -> Result<QueryPlan, String> {
+    let statement = query.trim().trim_end_matches(';');
+    
+    // VERBOSE adds each relation's schema, used to label nodes and look up table sizes
+    let explain = if analyze {
+        format!("EXPLAIN (FORMAT JSON, VERBOSE, ANALYZE, BUFFERS) {}", statement)
+    } else {
+        format!("EXPLAIN (FORMAT JSON, VERBOSE) {}", statement)
+    };
+    
+    // The extended protocol takes a single statement, so nothing can be appended after the one explained
+    let row = if analyze {
+        // ANALYZE really runs the statement; a session of its own keeps the rollback from touching
+        // anything else running on the connection
+        let mut session = open_session(connection_id).await?;
+        let transaction = session.transaction().await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let result = transaction.query_one(&explain, &[]).await;
+        transaction.rollback().await
+            .map_err(|e| format!("Failed to roll back transaction: {}", e))?;
+        result
+    } else {
+        get_client(connection_id)?.query_one(&explain, &[]).await
+    };
+    let JsonText(text) = row
+        .and_then(|row| row.try_get(0))
+        .map_err(|e| format!("Failed to explain query: {}", e))?;
+    
+    let output: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("Failed to parse plan: {}", e))?;
+    let output = output.get(0).ok_or_else(|| "EXPLAIN returned an empty plan".to_string())?;
+    let plan = output.get("Plan").ok_or_else(|| "EXPLAIN output has no plan".to_string())?;
+    
+    let mut root = parse_plan_node(plan, analyze);
+    if !analyze {
+        flag_large_seq_scans(&*get_client(connection_id)?, plan, &mut root).await?;
+    }
+    
+    Ok(QueryPlan {
+        root,
+        analyzed: analyze,
+        planning_time: output.get("Planning Time").and_then(Value::as_f64),
+        execution_time: output.get("Execution Time").and_then(Value::as_f64),
+    })
+}
+
+fn parse_plan_node(plan: &Value, analyzed: bool) -> PlanNode {
+    let number = |key: &str| plan.get(key).and_then(Value::as_f64);
+    let blocks = |key: &str| plan.get(key).and_then(Value::as_i64);
+    
+    let relation = plan.get("Relation Name").and_then(Value::as_str).map(|name| {
+        match plan.get("Schema").and_then(Value::as_str) {
+            Some(schema) => format!("{}.{}", schema, name),
+            None => name.to_string(),
+        }
+    });
+    
+    let details: Vec<String> = DETAIL_KEYS.iter().filter_map(|(key, label)| {
+        let value = match plan.get(*key)? {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items.iter()
+                .map(|item| item.as_str().map(str::to_string).unwrap_or_else(|| item.to_string()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            other => other.to_string(),
+        };
+        Some(format!("{}: {}", label, value))
+    }).collect();
+    
+    let mut node = PlanNode {
+        node_type: plan.get("Node Type").and_then(Value::as_str).unwrap_or("Unknown").to_string(),
+        relation,
+        detail: if details.is_empty() { None } else { Some(details.join("; ")) },
+        startup_cost: number("Startup Cost"),
+        total_cost: number("Total Cost"),
+        plan_rows: number("Plan Rows"),
+        actual_rows: number("Actual Rows"),
+        actual_loops: number("Actual Loops"),
+        actual_startup_time: number("Actual Startup Time"),
+        actual_total_time: number("Actual Total Time"),
+        shared_hit_blocks: blocks("Shared Hit Blocks"),
+        shared_read_blocks: blocks("Shared Read Blocks"),
+        temp_written_blocks: blocks("Temp Written Blocks"),
+        hot_spots: Vec::new(),
+        children: plan.get("Plans").and_then(Value::as_array)
+            .map(|plans| plans.iter().map(|child| parse_plan_node(child, analyzed)).collect())
+            .unwrap_or_default(),
+    };
+    
+    node.hot_spots = find_hot_spots(&node, number("Rows Removed by Filter"), analyzed);
+    node
+}
+
+// Without ANALYZE a seq scan's row estimate only counts the rows passing its filter, so a
+// selective filter hides a large scan; the table's own row estimate is checked instead.
+// reltuples is only as fresh as the last VACUUM or ANALYZE, and tables that have never been
+// analyzed (reltuples = -1) are judged by their plan rows alone.
+async fn flag_large_seq_scans(client: &Client, plan: &Value, root: &mut PlanNode) -> Result<(), String> {
+    let mut relations = Vec::new();
+    seq_scan_relations(plan, &mut relations);
+    if relations.is_empty() {
+        return Ok(());
+    }
+    
+    let (qualified, labels): (Vec<String>, Vec<String>) = relations.into_iter().unzip();
+    let query = "
+        SELECT r.name, c.reltuples::float8
+        FROM unnest($1::text[]) AS r(name)
+        JOIN pg_class c ON c.oid = to_regclass(r.name)";
+    let rows = match client.query(query, &[&qualified]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load table sizes: {}", e)),
+    };
+    
+    let mut table_rows = HashMap::new();
+    for row in rows {
+        let name: String = row.get(0);
+        if let Some(i) = qualified.iter().position(|q| *q == name) {
+            table_rows.insert(labels[i].clone(), row.get::<_, f64>(1));
+        }
+    }
+    mark_large_seq_scans(root, &table_rows);
+    
+    Ok(())
+}
+
+// Collects (quoted name, node label) for every relation read by a sequential scan
+fn seq_scan_relations(plan: &Value, relations: &mut Vec<(String, String)>) {
+    let node_type = plan.get("Node Type").and_then(Value::as_str);
+    let schema = plan.get("Schema").and_then(Value::as_str);
+    let name = plan.get("Relation Name").and_then(Value::as_str);
+    
+    if let (Some("Seq Scan" | "Parallel Seq Scan"), Some(schema), Some(name)) = (node_type, schema, name) {
+        relations.push((format!("{}.{}", quote_ident(schema), quote_ident(name)), format!("{}.{}", schema, name)));
+    }
+    for child in plan.get("Plans").and_then(Value::as_array).into_iter().flatten() {
+        seq_scan_relations(child, relations);
+    }
+}
+
+fn mark_large_seq_scans(node: &mut PlanNode, table_rows: &HashMap<String, f64>) {
+    let is_seq_scan = node.node_type == "Seq Scan" || node.node_type == "Parallel Seq Scan";
+    let rows = node.relation.as_ref().and_then(|relation| table_rows.get(relation));
+    
+    if is_seq_scan && rows.is_some_and(|rows| *rows >= SEQ_SCAN_HOT_ROWS) && !node.hot_spots.iter().any(|h| h == "seq_scan") {
+        node.hot_spots.insert(0, "seq_scan".to_string());
+    }
+    for child in &mut node.children {
+        mark_large_seq_scans(child, table_rows);
+    }
+}
+
+fn find_hot_spots(node: &PlanNode, rows_removed: Option<f64>, analyzed: bool) -> Vec<String> {
+    let mut hot_spots = Vec::new();
+    
+    if node.node_type == "Seq Scan" || node.node_type == "Parallel Seq Scan" {
+        // With ANALYZE count every row read, not just the ones that passed the filter; without it
+        // plan rows are a lower bound, and flag_large_seq_scans checks the table's size
+        let rows_read = if analyzed {
+            (node.actual_rows.unwrap_or(0.0) + rows_removed.unwrap_or(0.0)) * node.actual_loops.unwrap_or(1.0)
+        } else {
+            node.plan_rows.unwrap_or(0.0)
+        };
+        if rows_read >= SEQ_SCAN_HOT_ROWS {
+            hot_spots.push("seq_scan".to_string());
+        }
+    }
+    
+    // Nodes that never ran report zero loops and tell nothing about the estimate
+    if let (Some(planned), Some(actual), Some(loops)) = (node.plan_rows, node.actual_rows, node.actual_loops) {
+        let ratio = actual.max(1.0) / planned.max(1.0);
+        if loops > 0.0 && (ratio >= MISESTIMATE_FACTOR || ratio <= 1.0 / MISESTIMATE_FACTOR) {
+            hot_spots.push("row_misestimate".to_string());
+        }
+    }
+    
+    if node.temp_written_blocks.unwrap_or(0) > 0 {
+        hot_spots.push("disk_spill".to_string());
+    }
+    
+    hot_spots
+}
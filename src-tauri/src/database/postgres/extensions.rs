@@ -0,0 +1,78 @@
+use crate::database::models::ExtensionInfo;
+use super::{get_client, quote_ident, quote_literal};
+
+pub async fn list_extensions(connection_id: &str) -> Result<Vec<ExtensionInfo>, String> {
+    let client = get_client(connection_id)?;
+    
+    // Dotted numeric versions sort by their parts so 1.10 comes after 1.9; others sort as text after them
+    let query = "
+        SELECT a.name, e.extversion, a.default_version, n.nspname, a.comment,
+               ARRAY(SELECT v.version FROM pg_available_extension_versions v
+                     WHERE v.name = a.name
+                     ORDER BY CASE WHEN v.version ~ '^[0-9]+(\\.[0-9]+)*$'
+                                   THEN string_to_array(v.version, '.')::bigint[] END NULLS LAST,
+                              v.version)
+        FROM pg_available_extensions a
+        LEFT JOIN pg_extension e ON e.extname = a.name
+        LEFT JOIN pg_namespace n ON n.oid = e.extnamespace
+        ORDER BY e.extname IS NULL, a.name";
+    let rows = match client.query(query, &[]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to list extensions: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| {
+        let installed_version: Option<String> = row.get(1);
+        let default_version: Option<String> = row.get(2);
+        ExtensionInfo {
+            name: row.get(0),
+            update_available: installed_version.is_some() && installed_version != default_version,
+            installed_version,
+            default_version,
+            schema: row.get(3),
+            comment: row.get(4),
+            available_versions: row.get(5),
+        }
+    }).collect())
+}
+
+pub async fn create_extension(
+    connection_id: &str,
+    name: &str,
+    schema: Option<&str>,
+    version: Option<&str>,
+    cascade: bool,
+) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    
+    let mut statement = format!("CREATE EXTENSION IF NOT EXISTS {}", quote_ident(name));
+    if let Some(schema) = schema {
+        statement.push_str(&format!(" SCHEMA {}", quote_ident(schema)));
+    }
+    if let Some(version) = version {
+        statement.push_str(&format!(" VERSION {}", quote_literal(version)));
+    }
+    if cascade {
+        statement.push_str(" CASCADE");
+    }
+    
+    match client.batch_execute(&statement).await {
+        Ok(_) => Ok(statement),
+        Err(e) => Err(format!("Failed to create extension {}: {}", name, e)),
+    }
+}
+
+pub async fn update_extension(connection_id: &str, name: &str, version: Option<&str>) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    
+    // Without a version the extension moves to its default version
+    let statement = match version {
+        Some(version) => format!("ALTER EXTENSION {} UPDATE TO {}", quote_ident(name), quote_literal(version)),
+        None => format!("ALTER EXTENSION {} UPDATE", quote_ident(name)),
+    };
+    
+    match client.batch_execute(&statement).await {
+        Ok(_) => Ok(statement),
+        Err(e) => Err(format!("Failed to update extension {}: {}", name, e)),
+    }
+}
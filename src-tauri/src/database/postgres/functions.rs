@@ -0,0 +1,182 @@
+use crate::database::models::{FunctionArgument, FunctionInfo, QueryResult};
+use super::{execute_query, get_client, quote_ident, quote_literal};
+use serde_json::Value;
+use tokio_postgres::Client;
+
+pub async fn list_functions(connection_id: &str, schema: Option<&str>) -> Result<Vec<FunctionInfo>, String> {
+    let client = get_client(connection_id)?;
+    load_functions(&client, schema.unwrap_or("public"), None).await
+}
+
+pub async fn build_function_call(
+    connection_id: &str,
+    schema: Option<&str>,
+    name: &str,
+    args: &[Value],
+) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let schema_name = schema.unwrap_or("public");
+    
+    let mut functions = load_functions(&client, schema_name, Some(name)).await?;
+    let function = match functions.len() {
+        1 => functions.remove(0),
+        0 => return Err(format!("function {}.{} not found", schema_name, name)),
+        _ => return Err(format!("function {}.{} is overloaded, pass its signature", schema_name, name)),
+    };
+    
+    function_call(&function, args)
+}
+
+pub async fn execute_function(
+    connection_id: &str,
+    schema: Option<&str>,
+    name: &str,
+    args: &[Value],
+) -> Result<QueryResult, String> {
+    let statement = build_function_call(connection_id, schema, name, args).await?;
+    execute_query(connection_id.to_string(), statement).await
+}
+
+async fn load_functions(client: &Client, schema: &str, name: Option<&str>) -> Result<Vec<FunctionInfo>, String> {
+    // Argument names, types and modes come from the full argument list when the function
+    // has OUT arguments, otherwise every argument is an IN argument
+    let query = "
+        SELECT n.nspname, p.proname, p.oid::regprocedure::text,
+               pg_get_function_identity_arguments(p.oid), pg_get_function_result(p.oid),
+               p.prokind::text, l.lanname, p.provolatile::text, p.proisstrict, p.prosecdef, p.proretset,
+               obj_description(p.oid, 'pg_proc'), p.prosrc, p.pronargs, p.pronargdefaults,
+               args.names, args.types, args.modes
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        JOIN pg_language l ON l.oid = p.prolang
+        LEFT JOIN LATERAL (
+            SELECT array_agg(COALESCE(a.name, '') ORDER BY a.pos) AS names,
+                   array_agg(format_type(a.type, NULL) ORDER BY a.pos) AS types,
+                   array_agg(a.mode::text ORDER BY a.pos) AS modes
+            FROM unnest(COALESCE(p.proallargtypes, p.proargtypes::oid[]),
+                        COALESCE(p.proargmodes, array_fill('i'::\"char\", ARRAY[p.pronargs::int])),
+                        COALESCE(p.proargnames, '{}'::text[]))
+                 WITH ORDINALITY AS a(type, mode, name, pos)
+            WHERE a.type IS NOT NULL
+        ) args ON true
+        WHERE n.nspname = $1
+          AND ($2::text IS NULL OR p.proname = $2
+               OR p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')' = $2)
+        ORDER BY p.proname, 4";
+    let rows = match client.query(query, &[&schema, &name]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load functions: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| {
+        let input_count: i16 = row.get(13);
+        let default_count: i16 = row.get(14);
+        let names: Vec<String> = row.get::<_, Option<Vec<String>>>(15).unwrap_or_default();
+        let types: Vec<String> = row.get::<_, Option<Vec<String>>>(16).unwrap_or_default();
+        let modes: Vec<String> = row.get::<_, Option<Vec<String>>>(17).unwrap_or_default();
+        
+        // Defaults always belong to the trailing input arguments
+        let mut input_index = 0;
+        let arguments = names.into_iter().zip(types).zip(modes).map(|((name, data_type), mode)| {
+            let mode = match mode.as_str() {
+                "o" => "out",
+                "b" => "inout",
+                "v" => "variadic",
+                "t" => "table",
+                _ => "in",
+            };
+            let is_input = matches!(mode, "in" | "inout" | "variadic");
+            let has_default = is_input && input_index >= input_count - default_count;
+            if is_input {
+                input_index += 1;
+            }
+            FunctionArgument {
+                name: if name.is_empty() { None } else { Some(name) },
+                data_type,
+                mode: mode.to_string(),
+                has_default,
+            }
+        }).collect();
+        
+        FunctionInfo {
+            schema: row.get(0),
+            name: row.get(1),
+            signature: row.get(2),
+            identity_arguments: row.get(3),
+            result_type: row.get(4),
+            kind: match row.get::<_, String>(5).as_str() {
+                "p" => "procedure",
+                "a" => "aggregate",
+                "w" => "window",
+                _ => "function",
+            }.to_string(),
+            language: row.get(6),
+            volatility: match row.get::<_, String>(7).as_str() {
+                "i" => "immutable",
+                "s" => "stable",
+                _ => "volatile",
+            }.to_string(),
+            strict: row.get(8),
+            security_definer: row.get(9),
+            returns_set: row.get(10),
+            comment: row.get(11),
+            source: row.get(12),
+            arguments,
+        }
+    }).collect())
+}
+
+fn function_call(function: &FunctionInfo, args: &[Value]) -> Result<String, String> {
+    let is_procedure = function.kind == "procedure";
+    if !is_procedure && function.kind != "function" {
+        return Err(format!("Cannot call {} {} directly", function.kind, function.signature));
+    }
+    
+    // Functions take only their input arguments; procedures also take a placeholder for each OUT argument
+    let parameters: Vec<&FunctionArgument> = function.arguments.iter()
+        .filter(|a| a.mode != "table" && (is_procedure || a.mode != "out"))
+        .collect();
+    
+    if args.len() > parameters.len() {
+        return Err(format!("{} takes at most {} arguments", function.signature, parameters.len()));
+    }
+    
+    let mut values = Vec::new();
+    for (i, parameter) in parameters.iter().enumerate() {
+        let value = match args.get(i) {
+            Some(value) => value,
+            // Trailing arguments with defaults may be left out
+            None if parameter.has_default => break,
+            None if parameter.mode == "out" => &Value::Null,
+            None => return Err(format!("Missing value for argument {}", parameter.name.as_deref().unwrap_or(&(i + 1).to_string()))),
+        };
+        
+        let is_json = matches!(parameter.data_type.as_str(), "json" | "jsonb");
+        let literal = argument_literal(value, is_json);
+        let variadic = if parameter.mode == "variadic" { "VARIADIC " } else { "" };
+        values.push(format!("{}{}::{}", variadic, literal, parameter.data_type));
+    }
+    
+    // Always schema-qualified, since the signature drops the schema for functions on the search path
+    let name = format!("{}.{}", quote_ident(&function.schema), quote_ident(&function.name));
+    
+    Ok(if is_procedure {
+        format!("CALL {}({})", name, values.join(", "))
+    } else {
+        format!("SELECT * FROM {}({})", name, values.join(", "))
+    })
+}
+
+// JSON arrays become ARRAY[...] constructors, which the cast turns into any array type;
+// json and jsonb arguments take the value's own JSON text instead
+fn argument_literal(value: &Value, is_json: bool) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => quote_literal(s),
+        Value::Array(items) if !is_json => {
+            let elements: Vec<String> = items.iter().map(|item| argument_literal(item, false)).collect();
+            format!("ARRAY[{}]", elements.join(", "))
+        },
+        other => quote_literal(&other.to_string()),
+    }
+}
@@ -0,0 +1,284 @@
+use super::{get_client, row_value};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+use tokio_postgres::Row;
+
+// EWKB type flags set by PostGIS on top of the base geometry type
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+// PostGIS types sent as EWKB in binary results
+pub fn is_spatial(ty: &Type) -> bool {
+    ty.name() == "geometry" || ty.name() == "geography"
+}
+
+// A geometry column decoded straight to GeoJSON
+struct GeoJson(Value);
+
+impl<'a> FromSql<'a> for GeoJson {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(GeoJson(ewkb_to_geojson(raw)?))
+    }
+    
+    fn accepts(ty: &Type) -> bool {
+        is_spatial(ty)
+    }
+}
+
+pub fn geometry_value(row: &Row, index: usize) -> Result<Value, String> {
+    match row.try_get::<_, Option<GeoJson>>(index) {
+        Ok(Some(GeoJson(geometry))) => Ok(geometry),
+        Ok(None) => Ok(Value::Null),
+        Err(e) => Err(format!("Failed to decode geometry in column {}: {}", row.columns()[index].name(), e)),
+    }
+}
+
+// Geometries the reader can't decode (curved types, for one) are marked in their own cell
+// instead of failing the whole result
+pub fn geometry_cell(row: &Row, index: usize) -> Value {
+    match geometry_value(row, index) {
+        Ok(geometry) => geometry,
+        Err(e) => json!(format!("<{}>", e)),
+    }
+}
+
+pub fn ewkb_to_geojson(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = EwkbReader { bytes, pos: 0, little_endian: true };
+    let (mut geometry, srid) = reader.geometry()?;
+    
+    // GeoJSON assumes WGS 84, so any SRID is carried along as a named CRS
+    if let (Some(srid), Value::Object(object)) = (srid, &mut geometry) {
+        object.insert("crs".to_string(), json!({
+            "type": "name",
+            "properties": { "name": format!("EPSG:{}", srid) },
+        }));
+    }
+    
+    Ok(geometry)
+}
+
+pub async fn export_geojson(connection_id: &str, query: &str, path: &str) -> Result<usize, String> {
+    let client = get_client(connection_id)?;
+    
+    // The first spatial column becomes the feature geometry, the rest its properties
+    let statement = client.prepare(query).await
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let geometry_index = match statement.columns().iter().position(|c| is_spatial(c.type_())) {
+        Some(index) => index,
+        None => return Err("Query has no geometry or geography column".to_string()),
+    };
+    
+    let rows = client.query(&statement, &[]).await
+        .map_err(|e| format!("Failed to execute query: {}", e))?;
+    
+    let mut features = Vec::new();
+    for row in &rows {
+        let mut properties = Map::new();
+        for (i, column) in row.columns().iter().enumerate() {
+            if i == geometry_index {
+                continue;
+            }
+            let value = if is_spatial(column.type_()) { geometry_cell(row, i) } else { row_value(row, i) };
+            properties.insert(column.name().to_string(), value);
+        }
+        
+        // A geometry that can't be decoded is exported as null, with the reason kept alongside
+        let geometry = match geometry_value(row, geometry_index) {
+            Ok(geometry) => geometry,
+            Err(e) => {
+                properties.insert("geometry_error".to_string(), json!(e));
+                Value::Null
+            },
+        };
+        features.push(json!({
+            "type": "Feature",
+            "geometry": geometry,
+            "properties": properties,
+        }));
+    }
+    
+    let count = features.len();
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    
+    let contents = serde_json::to_string_pretty(&collection)
+        .map_err(|e| format!("Failed to serialize GeoJSON: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    
+    Ok(count)
+}
+
+struct EwkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl EwkbReader<'_> {
+    fn geometry(&mut self) -> Result<(Value, Option<u32>), String> {
+        self.little_endian = match self.byte()? {
+            0 => false,
+            1 => true,
+            order => return Err(format!("Invalid byte order {}", order)),
+        };
+        
+        let raw_type = self.u32()?;
+        let srid = if raw_type & EWKB_SRID != 0 { Some(self.u32()?) } else { None };
+        
+        // ISO WKB encodes dimensions as thousands (1001 = Point Z) instead of flags
+        let iso_dims = (raw_type & 0x0FFF_FFFF) / 1000;
+        let has_z = raw_type & EWKB_Z != 0 || iso_dims == 1 || iso_dims == 3;
+        let has_m = raw_type & EWKB_M != 0 || iso_dims == 2 || iso_dims == 3;
+        let dims = 2 + has_z as usize + has_m as usize;
+        
+        let geometry = match (raw_type & 0x0FFF_FFFF) % 1000 {
+            1 => {
+                let point = self.point(dims, has_z)?;
+                // Empty points are written as NaN coordinates
+                if point.iter().all(|c| c.as_f64().is_none()) {
+                    json!({ "type": "Point", "coordinates": [] })
+                } else {
+                    json!({ "type": "Point", "coordinates": point })
+                }
+            },
+            2 => json!({ "type": "LineString", "coordinates": self.points(dims, has_z)? }),
+            3 => json!({ "type": "Polygon", "coordinates": self.rings(dims, has_z)? }),
+            4 => json!({ "type": "MultiPoint", "coordinates": self.members("coordinates")? }),
+            5 => json!({ "type": "MultiLineString", "coordinates": self.members("coordinates")? }),
+            6 => json!({ "type": "MultiPolygon", "coordinates": self.members("coordinates")? }),
+            7 => json!({ "type": "GeometryCollection", "geometries": self.members("")? }),
+            other => return Err(format!("Unsupported geometry type {}", other)),
+        };
+        
+        Ok((geometry, srid))
+    }
+    
+    // Members of multi geometries are complete WKB geometries; key picks the part to keep
+    fn members(&mut self, key: &str) -> Result<Vec<Value>, String> {
+        let count = self.u32()?;
+        let mut members = Vec::new();
+        for _ in 0..count {
+            let (member, _) = self.geometry()?;
+            members.push(if key.is_empty() { member } else { member[key].clone() });
+        }
+        Ok(members)
+    }
+    
+    fn rings(&mut self, dims: usize, has_z: bool) -> Result<Vec<Vec<Vec<f64>>>, String> {
+        let count = self.u32()?;
+        (0..count).map(|_| self.points(dims, has_z)).collect()
+    }
+    
+    fn points(&mut self, dims: usize, has_z: bool) -> Result<Vec<Vec<f64>>, String> {
+        let count = self.u32()?;
+        (0..count).map(|_| {
+            let point = self.point(dims, has_z)?;
+            Ok(point.iter().filter_map(Value::as_f64).collect())
+        }).collect()
+    }
+    
+    // GeoJSON has no measure axis, so M values are read and dropped
+    fn point(&mut self, dims: usize, has_z: bool) -> Result<Vec<Value>, String> {
+        let mut coordinates = Vec::new();
+        for i in 0..dims {
+            let value = self.f64()?;
+            if i < 2 || (i == 2 && has_z) {
+                coordinates.push(json!(value));
+            }
+        }
+        Ok(coordinates)
+    }
+    
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.pos).ok_or("Unexpected end of EWKB data")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+    
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes: [u8; 4] = self.take(4)?.try_into().map_err(|_| "Invalid EWKB data")?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+    
+    fn f64(&mut self) -> Result<f64, String> {
+        let bytes: [u8; 8] = self.take(8)?.try_into().map_err(|_| "Invalid EWKB data")?;
+        Ok(if self.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) })
+    }
+    
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self.pos + len;
+        let slice = self.bytes.get(self.pos..end).ok_or("Unexpected end of EWKB data")?;
+        self.pos = end;
+        Ok(slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn ewkb(hex: &str) -> Result<Value, String> {
+        let bytes = (0..hex.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        ewkb_to_geojson(&bytes)
+    }
+    
+    #[test]
+    fn reads_points_with_srid_and_dimensions() {
+        // SRID=4326;POINT(1 2)
+        assert_eq!(ewkb("0101000020E6100000000000000000F03F0000000000000040").unwrap(), json!({
+            "type": "Point",
+            "coordinates": [1.0, 2.0],
+            "crs": { "type": "name", "properties": { "name": "EPSG:4326" } },
+        }));
+        // POINT Z (1 2 3)
+        assert_eq!(
+            ewkb("0101000080000000000000F03F00000000000000400000000000000840").unwrap(),
+            json!({ "type": "Point", "coordinates": [1.0, 2.0, 3.0] })
+        );
+        // POINT EMPTY
+        assert_eq!(
+            ewkb("0101000000000000000000F87F000000000000F87F").unwrap(),
+            json!({ "type": "Point", "coordinates": [] })
+        );
+    }
+    
+    #[test]
+    fn reads_lines_polygons_and_collections() {
+        // LINESTRING(0 0,1 1), big endian
+        assert_eq!(
+            ewkb("000000000200000002000000000000000000000000000000003FF00000000000003FF0000000000000").unwrap(),
+            json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] })
+        );
+        // POLYGON((0 0,1 0,1 1,0 0))
+        assert_eq!(
+            ewkb("0103000000010000000400000000000000000000000000000000000000000000000000F03F0000000000000000000000000000F03F000000000000F03F00000000000000000000000000000000").unwrap(),
+            json!({ "type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]] })
+        );
+        // SRID=3857;MULTIPOINT((1 2),(3 4))
+        assert_eq!(
+            ewkb("0104000020110F0000020000000101000000000000000000F03F0000000000000040010100000000000000000008400000000000001040").unwrap()["coordinates"],
+            json!([[1.0, 2.0], [3.0, 4.0]])
+        );
+        // GEOMETRYCOLLECTION(POINT(1 2))
+        assert_eq!(
+            ewkb("0107000000010000000101000000000000000000F03F0000000000000040").unwrap(),
+            json!({ "type": "GeometryCollection", "geometries": [{ "type": "Point", "coordinates": [1.0, 2.0] }] })
+        );
+    }
+    
+    #[test]
+    fn rejects_truncated_and_unsupported_geometries() {
+        assert_eq!(ewkb("0101000000000000"), Err("Unexpected end of EWKB data".to_string()));
+        assert_eq!(ewkb("0108000000"), Err("Unsupported geometry type 8".to_string()));
+        assert_eq!(ewkb("0201000000"), Err("Invalid byte order 2".to_string()));
+        // A huge point count must fail cleanly instead of over-reading
+        assert!(ewkb("010200000000FFFFFF").is_err());
+    }
+}
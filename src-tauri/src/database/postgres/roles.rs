@@ -0,0 +1,330 @@
+use crate::database::models::{DatabaseObject, EffectivePrivilege, GrantRequest, PrivilegeInfo, RoleInfo};
+use super::ddl::RELATION_KINDS;
+use super::{get_client, quote_ident};
+use tokio_postgres::Client;
+use tokio_postgres::types::Oid;
+
+// Privileges that can be granted on each object class
+const CLASS_PRIVILEGES: &[(&str, &[&str])] = &[
+    ("table", &["SELECT", "INSERT", "UPDATE", "DELETE", "TRUNCATE", "REFERENCES", "TRIGGER"]),
+    ("sequence", &["USAGE", "SELECT", "UPDATE"]),
+    ("function", &["EXECUTE"]),
+    ("schema", &["USAGE", "CREATE"]),
+];
+
+// A catalog object resolved from a DatabaseObject
+struct ResolvedObject {
+    oid: Oid,
+    // Qualified and quoted name usable in GRANT statements
+    sql_name: String,
+    // Privilege class: table, sequence, function or schema
+    class: &'static str,
+    // Object type keyword for GRANT ... ON
+    keyword: &'static str,
+}
+
+pub async fn list_roles(connection_id: &str) -> Result<Vec<RoleInfo>, String> {
+    let client = get_client(connection_id)?;
+    
+    let query = "
+        SELECT r.rolname, r.rolsuper, r.rolinherit, r.rolcreaterole, r.rolcreatedb, r.rolcanlogin,
+               r.rolreplication, r.rolbypassrls, r.rolconnlimit, r.rolvaliduntil::text,
+               ARRAY(SELECT g.rolname::text FROM pg_auth_members m JOIN pg_roles g ON g.oid = m.roleid
+                     WHERE m.member = r.oid ORDER BY 1),
+               ARRAY(SELECT u.rolname::text FROM pg_auth_members m JOIN pg_roles u ON u.oid = m.member
+                     WHERE m.roleid = r.oid ORDER BY 1)
+        FROM pg_roles r
+        WHERE r.rolname !~ '^pg_'
+        ORDER BY r.rolname";
+    let rows = match client.query(query, &[]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to list roles: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| RoleInfo {
+        name: row.get(0),
+        superuser: row.get(1),
+        inherit: row.get(2),
+        create_role: row.get(3),
+        create_db: row.get(4),
+        can_login: row.get(5),
+        replication: row.get(6),
+        bypass_rls: row.get(7),
+        connection_limit: row.get(8),
+        valid_until: row.get(9),
+        member_of: row.get(10),
+        members: row.get(11),
+    }).collect())
+}
+
+pub async fn get_privileges(connection_id: &str, object: &DatabaseObject) -> Result<Vec<PrivilegeInfo>, String> {
+    let client = get_client(connection_id)?;
+    let resolved = resolve_object(&client, object).await?;
+    
+    // Objects without an ACL use the built-in defaults, which aclexplode needs spelled out
+    let (catalog, acl, owner, default_type) = match resolved.class {
+        "schema" => ("pg_namespace", "nspacl", "nspowner", "n"),
+        "function" => ("pg_proc", "proacl", "proowner", "f"),
+        "sequence" => ("pg_class", "relacl", "relowner", "s"),
+        _ => ("pg_class", "relacl", "relowner", "r"),
+    };
+    let query = format!(
+        "SELECT CASE WHEN a.grantee = 0 THEN 'PUBLIC' ELSE pg_get_userbyid(a.grantee)::text END, \
+                pg_get_userbyid(a.grantor)::text, a.privilege_type, a.is_grantable \
+         FROM {catalog} o, aclexplode(COALESCE(o.{acl}, acldefault('{default_type}', o.{owner}))) a \
+         WHERE o.oid = $1 \
+         ORDER BY 1, 3",
+        catalog = catalog,
+        acl = acl,
+        owner = owner,
+        default_type = default_type,
+    );
+    let rows = match client.query(&query, &[&resolved.oid]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load privileges: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| PrivilegeInfo {
+        grantee: row.get(0),
+        grantor: row.get(1),
+        privilege: row.get(2),
+        grantable: row.get(3),
+    }).collect())
+}
+
+pub async fn get_effective_privileges(
+    connection_id: &str,
+    object: &DatabaseObject,
+    role: Option<&str>,
+) -> Result<Vec<EffectivePrivilege>, String> {
+    let client = get_client(connection_id)?;
+    let resolved = resolve_object(&client, object).await?;
+    
+    // Inherited memberships and superuser status are taken into account by the has_*_privilege functions
+    let function = match resolved.class {
+        "schema" => "has_schema_privilege",
+        "function" => "has_function_privilege",
+        "sequence" => "has_sequence_privilege",
+        _ => "has_table_privilege",
+    };
+    let query = format!(
+        "SELECT {}(COALESCE($1::text, current_user::text)::name, $2::oid, $3)",
+        function
+    );
+    
+    let mut privileges = Vec::new();
+    for privilege in class_privileges(resolved.class) {
+        let granted: bool = match client.query_one(&query, &[&role, &resolved.oid, privilege]).await {
+            Ok(row) => row.get(0),
+            Err(e) => return Err(format!("Failed to check privilege {}: {}", privilege, e)),
+        };
+        privileges.push(EffectivePrivilege {
+            privilege: privilege.to_string(),
+            granted,
+        });
+    }
+    
+    Ok(privileges)
+}
+
+pub async fn generate_grant(connection_id: &str, request: &GrantRequest) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let resolved = resolve_object(&client, &request.object).await?;
+    build_grant(&resolved, request)
+}
+
+pub async fn apply_grant(connection_id: &str, request: &GrantRequest) -> Result<String, String> {
+    let client = get_client(connection_id)?;
+    let resolved = resolve_object(&client, &request.object).await?;
+    let statement = build_grant(&resolved, request)?;
+    
+    match client.batch_execute(&statement).await {
+        Ok(_) => Ok(statement),
+        Err(e) => Err(format!("Failed to apply {}: {}", statement, e)),
+    }
+}
+
+fn build_grant(object: &ResolvedObject, request: &GrantRequest) -> Result<String, String> {
+    if request.roles.is_empty() {
+        return Err("At least one role is required".to_string());
+    }
+    if request.privileges.is_empty() {
+        return Err("At least one privilege is required".to_string());
+    }
+    
+    // Only known privilege keywords go into the statement, everything else is rejected
+    let allowed = class_privileges(object.class);
+    let mut privileges = Vec::new();
+    for privilege in &request.privileges {
+        let privilege = privilege.trim().to_uppercase();
+        if privilege == "ALL" || privilege == "ALL PRIVILEGES" {
+            privileges = vec!["ALL PRIVILEGES".to_string()];
+            break;
+        }
+        if !allowed.contains(&privilege.as_str()) {
+            return Err(format!("Privilege {} does not apply to {}", privilege, object.keyword));
+        }
+        privileges.push(privilege);
+    }
+    
+    let roles = request.roles.iter().map(|role| {
+        if role.eq_ignore_ascii_case("public") {
+            "PUBLIC".to_string()
+        } else {
+            quote_ident(role)
+        }
+    }).collect::<Vec<_>>().join(", ");
+    
+    let statement = match request.action.to_lowercase().as_str() {
+        "grant" => format!(
+            "GRANT {} ON {} {} TO {}{};",
+            privileges.join(", "),
+            object.keyword,
+            object.sql_name,
+            roles,
+            if request.with_grant_option.unwrap_or(false) { " WITH GRANT OPTION" } else { "" }
+        ),
+        "revoke" => format!(
+            "REVOKE {}{} ON {} {} FROM {}{};",
+            if request.with_grant_option.unwrap_or(false) { "GRANT OPTION FOR " } else { "" },
+            privileges.join(", "),
+            object.keyword,
+            object.sql_name,
+            roles,
+            if request.cascade.unwrap_or(false) { " CASCADE" } else { "" }
+        ),
+        action => return Err(format!("Unsupported action: {}", action)),
+    };
+    
+    Ok(statement)
+}
+
+fn class_privileges(class: &str) -> &'static [&'static str] {
+    CLASS_PRIVILEGES.iter()
+        .find(|(c, _)| *c == class)
+        .map(|(_, privileges)| *privileges)
+        .unwrap_or(&[])
+}
+
+async fn resolve_object(client: &Client, object: &DatabaseObject) -> Result<ResolvedObject, String> {
+    let schema = object.schema.as_deref().unwrap_or("public");
+    
+    match object.kind.as_str() {
+        "schema" => {
+            let query = "SELECT oid, quote_ident(nspname) FROM pg_namespace WHERE nspname = $1";
+            match client.query_opt(query, &[&object.name]).await {
+                Ok(Some(row)) => Ok(ResolvedObject {
+                    oid: row.get(0),
+                    sql_name: row.get(1),
+                    class: "schema",
+                    keyword: "SCHEMA",
+                }),
+                Ok(None) => Err(format!("schema {} not found", object.name)),
+                Err(e) => Err(format!("Failed to look up schema: {}", e)),
+            }
+        },
+        "function" | "procedure" => {
+            // Overloads share a name, so a bare name must be unambiguous
+            let query = "
+                SELECT p.oid, p.oid::regprocedure::text, p.prokind::text
+                FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace
+                WHERE n.nspname = $1 AND p.prokind IN ('f', 'p')
+                  AND (p.proname = $2 OR p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')' = $2)";
+            let rows = match client.query(query, &[&schema, &object.name]).await {
+                Ok(rows) => rows,
+                Err(e) => return Err(format!("Failed to look up function: {}", e)),
+            };
+            
+            match rows.as_slice() {
+                [row] => Ok(ResolvedObject {
+                    oid: row.get(0),
+                    sql_name: row.get(1),
+                    class: "function",
+                    keyword: if row.get::<_, String>(2) == "p" { "PROCEDURE" } else { "FUNCTION" },
+                }),
+                [] => Err(format!("function {}.{} not found", schema, object.name)),
+                _ => Err(format!("function {}.{} is overloaded, pass its signature", schema, object.name)),
+            }
+        },
+        kind => {
+            let relkinds = match RELATION_KINDS.iter().find(|(k, _)| *k == kind && *k != "index") {
+                Some((_, relkinds)) => *relkinds,
+                None => return Err(format!("Unsupported object kind: {}", kind)),
+            };
+            
+            let query = "SELECT c.oid, c.oid::regclass::text FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                         WHERE n.nspname = $1 AND c.relname = $2 AND strpos($3, c.relkind::text) > 0";
+            match client.query_opt(query, &[&schema, &object.name, &relkinds]).await {
+                Ok(Some(row)) => {
+                    let (class, keyword) = if kind == "sequence" { ("sequence", "SEQUENCE") } else { ("table", "TABLE") };
+                    Ok(ResolvedObject {
+                        oid: row.get(0),
+                        sql_name: row.get(1),
+                        class,
+                        keyword,
+                    })
+                },
+                Ok(None) => Err(format!("{} {}.{} not found", kind, schema, object.name)),
+                Err(e) => Err(format!("Failed to look up {}: {}", kind, e)),
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn table() -> ResolvedObject {
+        ResolvedObject { oid: 0, sql_name: "\"public\".\"users\"".to_string(), class: "table", keyword: "TABLE" }
+    }
+    
+    fn request(action: &str, privileges: &[&str], roles: &[&str]) -> GrantRequest {
+        GrantRequest {
+            action: action.to_string(),
+            privileges: privileges.iter().map(|p| p.to_string()).collect(),
+            object: DatabaseObject { schema: Some("public".to_string()), name: "users".to_string(), kind: "table".to_string() },
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            with_grant_option: None,
+            cascade: None,
+        }
+    }
+    
+    #[test]
+    fn builds_grant_and_revoke_statements() {
+        let mut grant = request("grant", &["select", " Insert "], &["app_user", "public"]);
+        grant.with_grant_option = Some(true);
+        assert_eq!(
+            build_grant(&table(), &grant).unwrap(),
+            "GRANT SELECT, INSERT ON TABLE \"public\".\"users\" TO \"app_user\", PUBLIC WITH GRANT OPTION;"
+        );
+        
+        let mut revoke = request("REVOKE", &["all"], &["Report\"Role"]);
+        revoke.with_grant_option = Some(true);
+        revoke.cascade = Some(true);
+        assert_eq!(
+            build_grant(&table(), &revoke).unwrap(),
+            "REVOKE GRANT OPTION FOR ALL PRIVILEGES ON TABLE \"public\".\"users\" FROM \"Report\"\"Role\" CASCADE;"
+        );
+    }
+    
+    #[test]
+    fn rejects_privileges_outside_the_class_whitelist() {
+        assert!(build_grant(&table(), &request("grant", &["EXECUTE"], &["app_user"])).is_err());
+        assert!(build_grant(&table(), &request("grant", &["SELECT ON pg_authid TO evil; --"], &["app_user"])).is_err());
+        
+        let function = ResolvedObject { oid: 0, sql_name: "\"public\".\"f\"()".to_string(), class: "function", keyword: "FUNCTION" };
+        assert!(build_grant(&function, &request("grant", &["SELECT"], &["app_user"])).is_err());
+        assert!(build_grant(&function, &request("grant", &["execute"], &["app_user"])).is_ok());
+    }
+    
+    #[test]
+    fn rejects_incomplete_requests() {
+        assert!(build_grant(&table(), &request("grant", &["SELECT"], &[])).is_err());
+        assert!(build_grant(&table(), &request("grant", &[], &["app_user"])).is_err());
+        assert_eq!(
+            build_grant(&table(), &request("alter", &["SELECT"], &["app_user"])),
+            Err("Unsupported action: alter".to_string())
+        );
+    }
+}
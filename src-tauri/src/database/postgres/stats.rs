@@ -0,0 +1,114 @@
+use crate::database::models::{DatabaseSize, TableStats};
+use super::get_client;
+
+pub async fn table_stats(connection_id: &str, schema: Option<&str>, table: Option<&str>) -> Result<Vec<TableStats>, String> {
+    let client = get_client(connection_id)?;
+    let schema_name = schema.unwrap_or("public");
+    
+    // Bloat is estimated from planner statistics: the pages the live rows should need at the
+    // table's fillfactor against the pages it actually has. Tables never analyzed report no estimate.
+    let query = "
+        WITH widths AS (
+            SELECT s.schemaname, s.tablename,
+                   24 + sum((1 - s.null_frac) * s.avg_width) AS row_width
+            FROM pg_stats s
+            WHERE s.schemaname = $1
+            GROUP BY s.schemaname, s.tablename
+        ),
+        expected AS (
+            SELECT c.oid,
+                   ceil(c.reltuples * (ceil(w.row_width / 8) * 8 + 4)
+                        / (current_setting('block_size')::numeric
+                           * COALESCE((SELECT substring(o FROM 'fillfactor=(\\d+)')::int
+                                       FROM unnest(c.reloptions) o WHERE o LIKE 'fillfactor=%'), 100) / 100 - 24)) AS pages
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            JOIN widths w ON w.schemaname = n.nspname AND w.tablename = c.relname
+            WHERE c.reltuples > 0
+        )
+        SELECT n.nspname, c.relname,
+               pg_total_relation_size(c.oid), pg_relation_size(c.oid), pg_indexes_size(c.oid),
+               COALESCE(pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0),
+               s.n_live_tup, s.n_dead_tup,
+               s.last_vacuum::text, s.last_autovacuum::text, s.last_analyze::text, s.last_autoanalyze::text,
+               s.seq_scan, s.seq_tup_read, s.idx_scan, s.idx_tup_fetch,
+               CASE WHEN e.pages IS NOT NULL
+                    THEN (greatest(c.relpages - e.pages, 0) * current_setting('block_size')::numeric)::int8
+               END
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_stat_all_tables s ON s.relid = c.oid
+        LEFT JOIN expected e ON e.oid = c.oid
+        WHERE n.nspname = $1 AND c.relkind IN ('r', 'm', 'p')
+          AND ($2::text IS NULL OR c.relname = $2)
+        ORDER BY pg_total_relation_size(c.oid) DESC, c.relname";
+    let rows = match client.query(query, &[&schema_name, &table]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load table statistics: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| {
+        let table_size: i64 = row.get(3);
+        let estimated_bloat: Option<i64> = row.get(16);
+        TableStats {
+            schema: row.get(0),
+            name: row.get(1),
+            total_size: row.get(2),
+            table_size,
+            indexes_size: row.get(4),
+            toast_size: row.get(5),
+            live_tuples: row.get(6),
+            dead_tuples: row.get(7),
+            last_vacuum: row.get(8),
+            last_autovacuum: row.get(9),
+            last_analyze: row.get(10),
+            last_autoanalyze: row.get(11),
+            seq_scans: row.get(12),
+            seq_tuples_read: row.get(13),
+            index_scans: row.get(14),
+            index_tuples_fetched: row.get(15),
+            estimated_bloat,
+            bloat_ratio: match estimated_bloat {
+                Some(bloat) if table_size > 0 => Some(bloat as f64 / table_size as f64),
+                _ => None,
+            },
+        }
+    }).collect())
+}
+
+pub async fn database_sizes(connection_id: &str) -> Result<Vec<DatabaseSize>, String> {
+    let client = get_client(connection_id)?;
+    
+    // Databases the user can't connect to have no readable size
+    let query = "
+        SELECT d.datname, pg_get_userbyid(d.datdba)::text, pg_encoding_to_char(d.encoding)::text,
+               CASE WHEN has_database_privilege(d.oid, 'CONNECT') THEN pg_database_size(d.oid) END,
+               s.numbackends, s.xact_commit, s.xact_rollback, s.blks_read, s.blks_hit, s.deadlocks
+        FROM pg_database d
+        LEFT JOIN pg_stat_database s ON s.datid = d.oid
+        WHERE NOT d.datistemplate
+        ORDER BY d.datname";
+    let rows = match client.query(query, &[]).await {
+        Ok(rows) => rows,
+        Err(e) => return Err(format!("Failed to load database sizes: {}", e)),
+    };
+    
+    Ok(rows.iter().map(|row| {
+        let blocks_read: Option<i64> = row.get(7);
+        let blocks_hit: Option<i64> = row.get(8);
+        DatabaseSize {
+            name: row.get(0),
+            owner: row.get(1),
+            encoding: row.get(2),
+            size: row.get(3),
+            connections: row.get(4),
+            commits: row.get(5),
+            rollbacks: row.get(6),
+            cache_hit_ratio: match (blocks_read, blocks_hit) {
+                (Some(read), Some(hit)) if read + hit > 0 => Some(hit as f64 / (read + hit) as f64),
+                _ => None,
+            },
+            deadlocks: row.get(9),
+        }
+    }).collect())
+}
@@ -1,10 +1,58 @@
-use crate::database::{SQLITE_CONNECTIONS, generate_connection_id};
-use crate::database::models::QueryResult;
+use crate::database::{SQLITE_CONNECTIONS, SQLITE_WATCHERS, generate_connection_id};
+use crate::database::models::{QueryResult, PragmaInfo, SqlitePragmas, SqliteOptions, SqliteChangeEvent, PlanNode, QueryPlan};
 use rusqlite::{Connection, params};
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter};
 
-pub fn connect_sqlite(path: &str) -> Result<String, String> {
+mod functions;
+#[cfg(feature = "sqlite-session")]
+pub mod session;
+
+// Event emitted when another process commits to a watched SQLite database
+pub const SQLITE_CHANGED_EVENT: &str = "sqlite-changed";
+
+// Quiet period after the last file event before checking for a new commit
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+// Multi-word step kinds in EXPLAIN QUERY PLAN output, other steps use their first word
+const PLAN_STEP_TYPES: &[&str] = &[
+    "USE TEMP B-TREE",
+    "COMPOUND QUERY",
+    "LEFT-MOST SUBQUERY",
+    "CORRELATED SCALAR SUBQUERY",
+    "SCALAR SUBQUERY",
+    "CORRELATED LIST SUBQUERY",
+    "LIST SUBQUERY",
+    "MULTI-INDEX OR",
+    "UNION ALL",
+    "UNION USING TEMP B-TREE",
+];
+
+// Pragmas exposed by the inspector: (name, value type, persisted in the database file)
+const INSPECTED_PRAGMAS: &[(&str, &str, bool)] = &[
+    ("journal_mode", "text", true),
+    ("page_size", "integer", true),
+    ("cache_size", "integer", false),
+    ("user_version", "integer", true),
+    ("application_id", "integer", true),
+    ("foreign_keys", "boolean", false),
+    ("auto_vacuum", "integer", true),
+    ("synchronous", "integer", false),
+    ("encoding", "text", true),
+    ("busy_timeout", "integer", false),
+    ("temp_store", "integer", false),
+    ("locking_mode", "text", false),
+    ("recursive_triggers", "boolean", false),
+    ("secure_delete", "boolean", false),
+];
+
+pub fn connect_sqlite(path: &str, options: Option<&SqliteOptions>) -> Result<String, String> {
     let connection_path = Path::new(path);
     
     // Check if the file exists or if it's :memory:
@@ -17,6 +65,16 @@ pub fn connect_sqlite(path: &str) -> Result<String, String> {
         return Err(format!("Database file does not exist: {}", path));
     };
     
+    if let Some(options) = options {
+        apply_cipher_options(&connection, options)?;
+        
+        for group in options.functions.iter().flatten() {
+            functions::register_function_group(&connection, group)?;
+        }
+        
+        load_extensions(&connection, options.extensions.as_deref().unwrap_or_default())?;
+    }
+    
     let connection_id = generate_connection_id();
     
     // Store the connection
@@ -26,6 +84,98 @@ pub fn connect_sqlite(path: &str) -> Result<String, String> {
     Ok(connection_id)
 }
 
+fn apply_cipher_options(conn: &Connection, options: &SqliteOptions) -> Result<(), String> {
+    let key = match &options.key {
+        Some(key) => key,
+        None => return Ok(()),
+    };
+    
+    if !cfg!(feature = "sqlcipher") {
+        return Err("Encrypted databases require ZenTable to be built with the 'sqlcipher' feature".to_string());
+    }
+    
+    // The key must be the first statement executed on the connection
+    let key = cipher_key_value(key, options.raw_key.unwrap_or(false))?;
+    conn.pragma_update(None, "key", &key)
+        .map_err(|e| format!("Failed to set encryption key: {}", e))?;
+    
+    // Compatibility settings have to be applied before the database is first read
+    if let Some(version) = options.cipher_compatibility {
+        conn.pragma_update(None, "cipher_compatibility", version)
+            .map_err(|e| format!("Failed to set cipher_compatibility: {}", e))?;
+    }
+    if let Some(page_size) = options.cipher_page_size {
+        conn.pragma_update(None, "cipher_page_size", page_size)
+            .map_err(|e| format!("Failed to set cipher_page_size: {}", e))?;
+    }
+    if let Some(kdf_iter) = options.kdf_iter {
+        conn.pragma_update(None, "kdf_iter", kdf_iter)
+            .map_err(|e| format!("Failed to set kdf_iter: {}", e))?;
+    }
+    if let Some(algorithm) = &options.cipher_hmac_algorithm {
+        conn.pragma_update(None, "cipher_hmac_algorithm", algorithm)
+            .map_err(|e| format!("Failed to set cipher_hmac_algorithm: {}", e))?;
+    }
+    if let Some(algorithm) = &options.cipher_kdf_algorithm {
+        conn.pragma_update(None, "cipher_kdf_algorithm", algorithm)
+            .map_err(|e| format!("Failed to set cipher_kdf_algorithm: {}", e))?;
+    }
+    
+    // SQLCipher only reports a wrong key once the database is actually read
+    conn.query_row("SELECT count(*) FROM sqlite_master", params![], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Failed to decrypt database (wrong key or cipher settings?): {}", e))?;
+    
+    Ok(())
+}
+
+fn load_extensions(conn: &Connection, paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    
+    // Extension loading is only enabled while the configured extensions are loaded
+    unsafe {
+        let _guard = rusqlite::LoadExtensionGuard::new(conn)
+            .map_err(|e| format!("Failed to enable extension loading: {}", e))?;
+        
+        for path in paths {
+            conn.load_extension(path, None)
+                .map_err(|e| format!("Failed to load SQLite extension {}: {}", path, e))?;
+        }
+    }
+    
+    Ok(())
+}
+
+fn cipher_key_value(key: &str, raw_key: bool) -> Result<String, String> {
+    if !raw_key {
+        return Ok(key.to_string());
+    }
+    
+    // Raw keys are passed to SQLCipher as a blob literal: x'<hex>'
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Raw encryption keys must be hex encoded".to_string());
+    }
+    
+    Ok(format!("x'{}'", key))
+}
+
+pub fn rekey(connection_id: &str, new_key: &str, raw_key: bool) -> Result<(), String> {
+    if !cfg!(feature = "sqlcipher") {
+        return Err("Re-keying requires ZenTable to be built with the 'sqlcipher' feature".to_string());
+    }
+    
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    if let Some(conn) = connections.get(connection_id) {
+        let key = cipher_key_value(new_key, raw_key)?;
+        conn.pragma_update(None, "rekey", &key)
+            .map_err(|e| format!("Failed to change encryption key: {}", e))
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
 pub fn execute_query(connection_id: String, query: String) -> Result<QueryResult, String> {
     let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
     
@@ -87,6 +237,7 @@ pub fn execute_query(connection_id: String, query: String) -> Result<QueryResult
                 affected_rows: None,
                 success: true,
                 error: None,
+                notices: None,
             })
         } else {
             // Handle non-SELECT queries
@@ -97,6 +248,7 @@ pub fn execute_query(connection_id: String, query: String) -> Result<QueryResult
                     affected_rows: Some(affected as u64),
                     success: true,
                     error: None,
+                    notices: None,
                 }),
                 Err(e) => Err(format!("Failed to execute query: {}", e)),
             }
@@ -107,6 +259,11 @@ pub fn execute_query(connection_id: String, query: String) -> Result<QueryResult
 }
 
 pub fn close_connection(connection_id: &str) -> Result<(), String> {
+    // Stop watching the file and recording changes before the connection goes away
+    let _ = unwatch_file(connection_id);
+    #[cfg(feature = "sqlite-session")]
+    let _ = session::stop_session(connection_id);
+    
     let mut connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
     
     if connections.remove(connection_id).is_some() {
@@ -134,4 +291,311 @@ pub fn get_database_structure(connection_id: &str) -> Result<Vec<String>, String
     } else {
         Err(format!("Connection with ID {} not found", connection_id))
     }
-}
\ No newline at end of file
+}
+
+pub fn get_pragmas(connection_id: &str) -> Result<SqlitePragmas, String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    if let Some(conn) = connections.get(connection_id) {
+        let mut pragmas = Vec::new();
+        for (name, value_type, persistent) in INSPECTED_PRAGMAS {
+            pragmas.push(read_pragma(conn, name, value_type, *persistent)?);
+        }
+        
+        let page_count = read_pragma_i64(conn, "page_count")?;
+        let freelist_count = read_pragma_i64(conn, "freelist_count")?;
+        let page_size = read_pragma_i64(conn, "page_size")?;
+        
+        // In-memory and temporary databases report an empty path
+        let (file_size, wal_size) = match conn.path() {
+            Some(path) if !path.is_empty() => (
+                fs::metadata(path).ok().map(|m| m.len()),
+                fs::metadata(format!("{}-wal", path)).ok().map(|m| m.len()),
+            ),
+            _ => (None, None),
+        };
+        
+        Ok(SqlitePragmas {
+            pragmas,
+            page_count,
+            freelist_count,
+            page_size,
+            file_size,
+            wal_size,
+        })
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
+pub fn set_pragma(connection_id: &str, name: &str, value: Value) -> Result<PragmaInfo, String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    if let Some(conn) = connections.get(connection_id) {
+        // Only allow pragmas known to the inspector so arbitrary names can't be injected
+        let (name, value_type, persistent) = match INSPECTED_PRAGMAS.iter().find(|(n, _, _)| *n == name) {
+            Some(pragma) => *pragma,
+            None => return Err(format!("Unsupported pragma: {}", name)),
+        };
+        
+        let result = match (&value, value_type) {
+            (Value::Bool(b), "boolean") => conn.pragma_update(None, name, b),
+            (Value::Number(n), "boolean") => conn.pragma_update(None, name, n.as_i64().unwrap_or(0) != 0),
+            (Value::Number(n), "integer") => match n.as_i64() {
+                Some(i) => conn.pragma_update(None, name, i),
+                None => return Err(format!("Pragma {} expects an integer value", name)),
+            },
+            // SQLite reads non-numeric text as 0 for integer pragmas instead of rejecting it
+            (Value::String(s), "integer") => match s.trim().parse::<i64>() {
+                Ok(i) => conn.pragma_update(None, name, i),
+                Err(_) => return Err(format!("Pragma {} expects an integer value", name)),
+            },
+            (Value::String(s), _) => conn.pragma_update(None, name, s),
+            _ => return Err(format!("Invalid value for pragma {}: {}", name, value)),
+        };
+        
+        result.map_err(|e| format!("Failed to set pragma {}: {}", name, e))?;
+        
+        read_pragma(conn, name, value_type, persistent)
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
+fn read_pragma(conn: &Connection, name: &str, value_type: &str, persistent: bool) -> Result<PragmaInfo, String> {
+    let raw: rusqlite::types::Value = conn.pragma_query_value(None, name, |row| row.get(0))
+        .map_err(|e| format!("Failed to read pragma {}: {}", name, e))?;
+    
+    let value = match (raw, value_type) {
+        (rusqlite::types::Value::Integer(i), "boolean") => Value::Bool(i != 0),
+        (rusqlite::types::Value::Integer(i), _) => Value::Number(i.into()),
+        (rusqlite::types::Value::Text(s), _) => Value::String(s),
+        (rusqlite::types::Value::Real(f), _) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        _ => Value::Null,
+    };
+    
+    Ok(PragmaInfo {
+        name: name.to_string(),
+        value,
+        value_type: value_type.to_string(),
+        persistent,
+    })
+}
+
+fn read_pragma_i64(conn: &Connection, name: &str) -> Result<i64, String> {
+    conn.pragma_query_value(None, name, |row| row.get(0))
+        .map_err(|e| format!("Failed to read pragma {}: {}", name, e))
+}
+
+pub fn watch_file(app: AppHandle, connection_id: &str) -> Result<(), String> {
+    let (db_path, initial_version) = {
+        let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+        
+        match connections.get(connection_id) {
+            Some(conn) => {
+                let path = match conn.path() {
+                    Some(path) if !path.is_empty() => canonical_path(Path::new(path)),
+                    _ => return Err("In-memory databases cannot be watched".to_string()),
+                };
+                (path, read_pragma_i64(conn, "data_version")?)
+            },
+            None => return Err(format!("Connection with ID {} not found", connection_id)),
+        }
+    };
+    
+    // In WAL mode commits only touch the -wal file until a checkpoint runs
+    let mut wal_path = db_path.clone().into_os_string();
+    wal_path.push("-wal");
+    let wal_path = PathBuf::from(wal_path);
+    let event_path = db_path.to_string_lossy().into_owned();
+    
+    // The -wal file is created and removed on the fly, so watch the containing directory
+    let watch_dir = match db_path.parent() {
+        Some(dir) => dir.to_path_buf(),
+        None => return Err(format!("Cannot watch {}", db_path.display())),
+    };
+    
+    // File events arrive while a transaction is still being written, so they're forwarded to a
+    // worker thread that waits for the files to settle before checking for a new commit
+    let (sender, receiver) = mpsc::channel::<()>();
+    
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) => {
+                let touches_database = event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove();
+                let is_database = |p: &PathBuf| {
+                    let p = canonical_path(p);
+                    p == db_path || p == wal_path
+                };
+                if touches_database && event.paths.iter().any(is_database) {
+                    let _ = sender.send(());
+                }
+            },
+            Err(e) => eprintln!("SQLite file watcher error: {}", e),
+        }
+    }).map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    
+    let watched_id = connection_id.to_string();
+    let mut last_version = initial_version;
+    
+    // The worker exits once the watcher (and with it the sender) is dropped
+    thread::spawn(move || {
+        while receiver.recv().is_ok() {
+            while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+            
+            // data_version only changes when another connection commits, so it filters out
+            // our own writes as well as file events that don't represent a new transaction
+            let version = {
+                let connections = match SQLITE_CONNECTIONS.lock() {
+                    Ok(connections) => connections,
+                    Err(_) => return,
+                };
+                match connections.get(&watched_id).map(|conn| read_pragma_i64(conn, "data_version")) {
+                    Some(Ok(version)) => version,
+                    Some(Err(_)) => continue,
+                    None => return,
+                }
+            };
+            
+            if version != last_version {
+                last_version = version;
+                let payload = SqliteChangeEvent {
+                    connection_id: watched_id.clone(),
+                    path: event_path.clone(),
+                    data_version: version,
+                };
+                if let Err(e) = app.emit(SQLITE_CHANGED_EVENT, payload) {
+                    eprintln!("Failed to emit SQLite change event: {}", e);
+                }
+            }
+        }
+    });
+    
+    watcher.watch(&watch_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", watch_dir.display(), e))?;
+    
+    // Replacing an existing watcher drops it, which stops the old one
+    let mut watchers = SQLITE_WATCHERS.lock().map_err(|e| e.to_string())?;
+    watchers.insert(connection_id.to_string(), watcher);
+    
+    Ok(())
+}
+
+// Resolves symlinks and relative parts so watcher paths compare equal to the database's. Only
+// the directory is resolved, since the file itself may already be gone (a checkpointed -wal file).
+fn canonical_path(path: &Path) -> PathBuf {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match (fs::canonicalize(dir), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+pub fn unwatch_file(connection_id: &str) -> Result<(), String> {
+    let mut watchers = SQLITE_WATCHERS.lock().map_err(|e| e.to_string())?;
+    
+    if watchers.remove(connection_id).is_some() {
+        Ok(())
+    } else {
+        Err(format!("Connection with ID {} is not being watched", connection_id))
+    }
+}
+
+pub fn explain_query(connection_id: &str, query: &str) -> Result<QueryPlan, String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    if let Some(conn) = connections.get(connection_id) {
+        let explain = format!("EXPLAIN QUERY PLAN {}", query.trim().trim_end_matches(';'));
+        let mut stmt = match conn.prepare(&explain) {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(format!("Failed to prepare statement: {}", e)),
+        };
+        
+        // Rows are (id, parent, notused, detail), parents always listed before their children
+        let steps: Vec<(i64, i64, String)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))
+            .map_err(|e| format!("Failed to explain query: {}", e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read query plan: {}", e))?;
+        
+        let root = PlanNode {
+            node_type: "QUERY PLAN".to_string(),
+            children: plan_children(&steps, 0),
+            ..Default::default()
+        };
+        
+        // SQLite has no EXPLAIN ANALYZE, so only the plan shape is available
+        Ok(QueryPlan {
+            root,
+            analyzed: false,
+            planning_time: None,
+            execution_time: None,
+        })
+    } else {
+        Err(format!("Connection with ID {} not found", connection_id))
+    }
+}
+
+fn plan_children(steps: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+    steps.iter().filter(|(_, p, _)| *p == parent).map(|(id, _, detail)| {
+        let node_type = PLAN_STEP_TYPES.iter()
+            .find(|prefix| detail.starts_with(*prefix))
+            .map(|prefix| prefix.to_string())
+            .unwrap_or_else(|| detail.split_whitespace().next().unwrap_or("").to_string());
+        
+        // Older SQLite versions print "SCAN TABLE name" instead of "SCAN name"
+        let relation = if node_type == "SCAN" || node_type == "SEARCH" {
+            detail.split_whitespace().skip(1).find(|word| *word != "TABLE").map(str::to_string)
+        } else {
+            None
+        };
+        
+        let mut hot_spots = Vec::new();
+        if node_type == "SCAN" && !detail.contains(" USING ") && !detail.starts_with("SCAN CONSTANT ROW") {
+            hot_spots.push("seq_scan".to_string());
+        }
+        if node_type == "USE TEMP B-TREE" {
+            hot_spots.push("temp_b_tree".to_string());
+        }
+        
+        PlanNode {
+            node_type,
+            relation,
+            detail: Some(detail.clone()),
+            hot_spots,
+            children: plan_children(steps, *id),
+            ..Default::default()
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn integer_pragmas_reject_non_numeric_text() {
+        let id = connect_sqlite(":memory:", None).unwrap();
+        
+        assert_eq!(
+            set_pragma(&id, "cache_size", json!("abc")).unwrap_err(),
+            "Pragma cache_size expects an integer value"
+        );
+        assert!(set_pragma(&id, "user_version", json!(1.5)).is_err());
+        assert_eq!(set_pragma(&id, "cache_size", json!(" -4000 ")).unwrap().value, json!(-4000));
+        assert_eq!(set_pragma(&id, "busy_timeout", json!("2500")).unwrap().value, json!(2500));
+        assert_eq!(set_pragma(&id, "user_version", json!(7)).unwrap().value, json!(7));
+        
+        // Text and boolean pragmas keep taking their own value types
+        assert_eq!(set_pragma(&id, "locking_mode", json!("exclusive")).unwrap().value, json!("exclusive"));
+        assert_eq!(set_pragma(&id, "foreign_keys", json!(true)).unwrap().value, json!(true));
+        assert!(set_pragma(&id, "not_a_pragma", json!(1)).is_err());
+        
+        close_connection(&id).unwrap();
+    }
+}
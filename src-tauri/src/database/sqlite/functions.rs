@@ -0,0 +1,152 @@
+use rusqlite::Connection;
+use rusqlite::functions::{Context, FunctionFlags};
+use regex::Regex;
+use serde_json::Value;
+
+// Optional function groups that can be registered on a SQLite connection
+pub const FUNCTION_GROUPS: &[&str] = &["regexp", "uuid", "json", "math"];
+
+type UnaryMathFn = fn(f64) -> f64;
+type BinaryMathFn = fn(f64, f64) -> f64;
+
+const UNARY_MATH_FUNCTIONS: &[(&str, UnaryMathFn)] = &[
+    ("acos", f64::acos),
+    ("asin", f64::asin),
+    ("atan", f64::atan),
+    ("ceil", f64::ceil),
+    ("ceiling", f64::ceil),
+    ("cos", f64::cos),
+    ("cosh", f64::cosh),
+    ("degrees", f64::to_degrees),
+    ("exp", f64::exp),
+    ("floor", f64::floor),
+    ("ln", f64::ln),
+    ("log10", f64::log10),
+    ("log2", f64::log2),
+    ("radians", f64::to_radians),
+    ("sin", f64::sin),
+    ("sinh", f64::sinh),
+    ("sqrt", f64::sqrt),
+    ("tan", f64::tan),
+    ("tanh", f64::tanh),
+    ("trunc", f64::trunc),
+];
+
+const BINARY_MATH_FUNCTIONS: &[(&str, BinaryMathFn)] = &[
+    ("atan2", f64::atan2),
+    ("mod", |x, y| x % y),
+    ("pow", f64::powf),
+    ("power", f64::powf),
+];
+
+pub fn register_function_group(conn: &Connection, group: &str) -> Result<(), String> {
+    let result = match group {
+        "regexp" => register_regexp(conn),
+        "uuid" => register_uuid(conn),
+        "json" => register_json(conn),
+        "math" => register_math(conn),
+        _ => return Err(format!(
+            "Unknown SQLite function group: {} (expected one of {})",
+            group,
+            FUNCTION_GROUPS.join(", ")
+        )),
+    };
+    
+    result.map_err(|e| format!("Failed to register {} functions: {}", group, e))
+}
+
+fn deterministic() -> FunctionFlags {
+    FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC
+}
+
+fn register_regexp(conn: &Connection) -> rusqlite::Result<()> {
+    // `X REGEXP Y` is evaluated by SQLite as regexp(Y, X)
+    conn.create_scalar_function("regexp", 2, deterministic(), |ctx| {
+        // Cache the compiled pattern for the duration of the statement
+        let pattern = ctx.get_or_create_aux(0, |value| -> Result<Regex, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Regex::new(value.as_str()?)?)
+        })?;
+        
+        let text = match ctx.get_raw(1).as_str_or_null()? {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+        
+        Ok(Some(pattern.is_match(text)))
+    })
+}
+
+fn register_uuid(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function("uuid", 0, FunctionFlags::SQLITE_UTF8, |_| {
+        Ok(uuid::Uuid::new_v4().to_string())
+    })
+}
+
+fn register_json(conn: &Connection) -> rusqlite::Result<()> {
+    // json_pretty(json): reformat a JSON document with indentation
+    conn.create_scalar_function("json_pretty", 1, deterministic(), |ctx| {
+        let value = match parse_json_arg(ctx, 0)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        
+        serde_json::to_string_pretty(&value)
+            .map(Some)
+            .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+    })?;
+    
+    // json_keys(json): JSON array of the top-level keys of an object
+    conn.create_scalar_function("json_keys", 1, deterministic(), |ctx| {
+        let keys = match parse_json_arg(ctx, 0)? {
+            Some(Value::Object(map)) => Value::Array(map.keys().cloned().map(Value::String).collect()),
+            _ => return Ok(None),
+        };
+        
+        Ok(Some(keys.to_string()))
+    })
+}
+
+fn parse_json_arg(ctx: &Context<'_>, idx: usize) -> rusqlite::Result<Option<Value>> {
+    let text = match ctx.get_raw(idx).as_str_or_null()? {
+        Some(text) => text,
+        None => return Ok(None),
+    };
+    
+    serde_json::from_str(text)
+        .map(Some)
+        .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))
+}
+
+fn register_math(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function("pi", 0, deterministic(), |_| Ok(std::f64::consts::PI))?;
+    
+    for (name, func) in UNARY_MATH_FUNCTIONS {
+        let func = *func;
+        conn.create_scalar_function(name, 1, deterministic(), move |ctx| {
+            Ok(ctx.get::<Option<f64>>(0)?.map(func).filter(|v| v.is_finite()))
+        })?;
+    }
+    
+    for (name, func) in BINARY_MATH_FUNCTIONS {
+        let func = *func;
+        conn.create_scalar_function(name, 2, deterministic(), move |ctx| {
+            let result = match (ctx.get::<Option<f64>>(0)?, ctx.get::<Option<f64>>(1)?) {
+                (Some(x), Some(y)) => Some(func(x, y)),
+                _ => None,
+            };
+            Ok(result.filter(|v| v.is_finite()))
+        })?;
+    }
+    
+    // log(x) is base 10 and log(b, x) uses base b, matching SQLite's built-in math functions
+    conn.create_scalar_function("log", 1, deterministic(), |ctx| {
+        Ok(ctx.get::<Option<f64>>(0)?.map(f64::log10).filter(|v| v.is_finite()))
+    })?;
+    conn.create_scalar_function("log", 2, deterministic(), |ctx| {
+        let result = match (ctx.get::<Option<f64>>(0)?, ctx.get::<Option<f64>>(1)?) {
+            (Some(base), Some(x)) => Some(x.log(base)),
+            _ => None,
+        };
+        Ok(result.filter(|v| v.is_finite()))
+    })
+}
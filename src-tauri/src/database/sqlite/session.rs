@@ -0,0 +1,456 @@
+use crate::database::{SQLITE_CONNECTIONS, SQLITE_SESSIONS};
+use crate::database::models::ChangesetEntry;
+use rusqlite::Connection;
+use rusqlite::session::{self, ConflictAction, ConflictType, Session};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Cursor, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Operation codes used in the changeset format
+const OP_INSERT: u8 = 18;
+const OP_UPDATE: u8 = 23;
+const OP_DELETE: u8 = 9;
+
+// A session extension object recording changes made on a stored connection
+pub struct RecordingSession(Session<'static>);
+
+// SAFETY: the session only holds the raw sqlite3 handle of its connection. It is only used
+// while SQLITE_CONNECTIONS is locked and is dropped before its connection is closed.
+unsafe impl Send for RecordingSession {}
+
+pub fn start_session(connection_id: &str, tables: Option<Vec<String>>) -> Result<(), String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    let mut sessions = SQLITE_SESSIONS.lock().map_err(|e| e.to_string())?;
+    
+    let conn = match connections.get(connection_id) {
+        Some(conn) => conn,
+        None => return Err(format!("Connection with ID {} not found", connection_id)),
+    };
+    
+    if sessions.contains_key(connection_id) {
+        return Err(format!("A session is already recording on connection {}", connection_id));
+    }
+    
+    let mut session = Session::new(conn)
+        .map_err(|e| format!("Failed to create session: {}", e))?;
+    
+    // Attach the requested tables, or every table when none are given
+    match tables {
+        Some(tables) if !tables.is_empty() => {
+            for table in &tables {
+                session.attach(Some(table))
+                    .map_err(|e| format!("Failed to attach table {}: {}", table, e))?;
+            }
+        },
+        _ => session.attach(None).map_err(|e| format!("Failed to attach tables: {}", e))?,
+    }
+    
+    // SAFETY: see RecordingSession; close_connection stops the session before the
+    // connection is removed from SQLITE_CONNECTIONS.
+    let session = unsafe { std::mem::transmute::<Session<'_>, Session<'static>>(session) };
+    sessions.insert(connection_id.to_string(), RecordingSession(session));
+    
+    Ok(())
+}
+
+pub fn stop_session(connection_id: &str) -> Result<(), String> {
+    // The connection lock is held so the session is never deleted while the connection is in use
+    let _connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    let mut sessions = SQLITE_SESSIONS.lock().map_err(|e| e.to_string())?;
+    
+    if sessions.remove(connection_id).is_some() {
+        Ok(())
+    } else {
+        Err(format!("No session is recording on connection {}", connection_id))
+    }
+}
+
+pub fn export_session(connection_id: &str, path: &str, patchset: bool) -> Result<u64, String> {
+    let _connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    let mut sessions = SQLITE_SESSIONS.lock().map_err(|e| e.to_string())?;
+    
+    let session = match sessions.get_mut(connection_id) {
+        Some(session) => &mut session.0,
+        None => return Err(format!("No session is recording on connection {}", connection_id)),
+    };
+    
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    
+    let result = if patchset {
+        session.patchset_strm(&mut writer)
+    } else {
+        session.changeset_strm(&mut writer)
+    };
+    result.map_err(|e| format!("Failed to export changes: {}", e))?;
+    
+    writer.flush().map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    
+    fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+// Lists the changes recorded by the connection's session, or stored in a changeset file
+pub fn list_changes(connection_id: &str, path: Option<&str>) -> Result<Vec<ChangesetEntry>, String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    let conn = match connections.get(connection_id) {
+        Some(conn) => conn,
+        None => return Err(format!("Connection with ID {} not found", connection_id)),
+    };
+    
+    let data = match path {
+        Some(path) => fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+        None => {
+            let mut sessions = SQLITE_SESSIONS.lock().map_err(|e| e.to_string())?;
+            let session = match sessions.get_mut(connection_id) {
+                Some(session) => &mut session.0,
+                None => return Err(format!("No session is recording on connection {}", connection_id)),
+            };
+            
+            let mut data = Vec::new();
+            session.changeset_strm(&mut data)
+                .map_err(|e| format!("Failed to read session changes: {}", e))?;
+            data
+        },
+    };
+    
+    let mut entries = parse_changeset(&data)?;
+    
+    // The changeset format doesn't carry column names, so look them up on the connection
+    for entry in &mut entries {
+        entry.columns = table_columns(conn, &entry.table);
+    }
+    
+    Ok(entries)
+}
+
+pub fn apply_changeset(connection_id: &str, path: &str, invert: bool, on_conflict: Option<&str>) -> Result<u64, String> {
+    let connections = SQLITE_CONNECTIONS.lock().map_err(|e| e.to_string())?;
+    
+    let conn = match connections.get(connection_id) {
+        Some(conn) => conn,
+        None => return Err(format!("Connection with ID {} not found", connection_id)),
+    };
+    
+    let replace = match on_conflict.unwrap_or("omit") {
+        "omit" => Some(false),
+        "replace" => Some(true),
+        "abort" => None,
+        other => return Err(format!("Unsupported conflict action: {} (expected omit, replace or abort)", other)),
+    };
+    
+    let mut data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    
+    if invert {
+        let mut inverted = Vec::new();
+        session::invert_strm(&mut Cursor::new(&data), &mut inverted)
+            .map_err(|e| format!("Failed to invert changeset: {}", e))?;
+        data = inverted;
+    }
+    
+    let conflicts = Arc::new(AtomicU64::new(0));
+    let conflict_counter = Arc::clone(&conflicts);
+    
+    conn.apply_strm(
+        &mut Cursor::new(&data),
+        None::<fn(&str) -> bool>,
+        move |conflict_type, _item| {
+            conflict_counter.fetch_add(1, Ordering::SeqCst);
+            match (replace, conflict_type) {
+                (None, _) => ConflictAction::SQLITE_CHANGESET_ABORT,
+                // REPLACE is only valid for DATA and CONFLICT conflicts
+                (Some(true), ConflictType::SQLITE_CHANGESET_DATA)
+                | (Some(true), ConflictType::SQLITE_CHANGESET_CONFLICT) => ConflictAction::SQLITE_CHANGESET_REPLACE,
+                _ => ConflictAction::SQLITE_CHANGESET_OMIT,
+            }
+        },
+    ).map_err(|e| format!("Failed to apply changeset: {}", e))?;
+    
+    Ok(conflicts.load(Ordering::SeqCst))
+}
+
+pub fn invert_changeset(input_path: &str, output_path: &str) -> Result<(), String> {
+    let input = File::open(input_path).map_err(|e| format!("Failed to open {}: {}", input_path, e))?;
+    let output = File::create(output_path).map_err(|e| format!("Failed to create {}: {}", output_path, e))?;
+    
+    let mut reader = BufReader::new(input);
+    let mut writer = BufWriter::new(output);
+    
+    session::invert_strm(&mut reader, &mut writer)
+        .map_err(|e| format!("Failed to invert changeset: {}", e))?;
+    
+    writer.flush().map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Vec<String> {
+    let mut stmt = match conn.prepare("SELECT name FROM pragma_table_info(?1) ORDER BY cid") {
+        Ok(stmt) => stmt,
+        Err(_) => return Vec::new(),
+    };
+    
+    let names = stmt.query_map([table], |row| row.get(0))
+        .and_then(|rows| rows.collect::<Result<Vec<String>, _>>());
+    
+    names.unwrap_or_default()
+}
+
+// Decodes a changeset or patchset as described in https://sqlite.org/session/changeset_format.html.
+// rusqlite's iterator can't be used for this since it dereferences the missing values of UPDATEs.
+fn parse_changeset(data: &[u8]) -> Result<Vec<ChangesetEntry>, String> {
+    let mut reader = ChangesetReader { data, pos: 0 };
+    let mut entries = Vec::new();
+    
+    let mut table = String::new();
+    let mut primary_key: Vec<bool> = Vec::new();
+    let mut patchset = false;
+    
+    while reader.pos < data.len() {
+        let op = reader.byte()?;
+        
+        match op {
+            b'T' | b'P' => {
+                patchset = op == b'P';
+                let column_count = reader.varint()? as usize;
+                primary_key = reader.bytes(column_count)?.iter().map(|b| *b != 0).collect();
+                table = reader.nul_terminated()?;
+                continue;
+            },
+            OP_INSERT | OP_UPDATE | OP_DELETE => {},
+            other => return Err(format!("Invalid changeset: unexpected byte 0x{:02x}", other)),
+        }
+        
+        let indirect = reader.byte()? != 0;
+        let column_count = primary_key.len();
+        let pk_count = primary_key.iter().filter(|pk| **pk).count();
+        
+        let (old_values, new_values) = match (op, patchset) {
+            (OP_INSERT, _) => (Vec::new(), reader.record(column_count)?),
+            (OP_DELETE, false) => (reader.record(column_count)?, Vec::new()),
+            // Patchsets only store the primary key of deleted rows
+            (OP_DELETE, true) => (spread_primary_key(&primary_key, reader.record(pk_count)?), Vec::new()),
+            (_, false) => {
+                let old = reader.record(column_count)?;
+                (old, reader.record(column_count)?)
+            },
+            // Patchset updates have a single record holding the primary key and new values
+            (_, true) => {
+                let record = reader.record(column_count)?;
+                let old = record.iter().zip(&primary_key)
+                    .map(|(value, pk)| if *pk { value.clone() } else { None })
+                    .collect();
+                (old, record)
+            },
+        };
+        
+        let changed_columns = match op {
+            OP_INSERT => (0..column_count).collect(),
+            OP_DELETE => (0..column_count).collect(),
+            _ => (0..column_count)
+                .filter(|i| !primary_key[*i] && matches!(new_values.get(*i), Some(Some(_))))
+                .collect(),
+        };
+        
+        entries.push(ChangesetEntry {
+            table: table.clone(),
+            operation: match op {
+                OP_INSERT => "INSERT",
+                OP_DELETE => "DELETE",
+                _ => "UPDATE",
+            }.to_string(),
+            indirect,
+            columns: Vec::new(),
+            primary_key: (0..column_count).filter(|i| primary_key[*i]).collect(),
+            old_values: old_values.into_iter().map(|v| v.unwrap_or(Value::Null)).collect(),
+            new_values: new_values.into_iter().map(|v| v.unwrap_or(Value::Null)).collect(),
+            changed_columns,
+        });
+    }
+    
+    Ok(entries)
+}
+
+fn spread_primary_key(primary_key: &[bool], values: Vec<Option<Value>>) -> Vec<Option<Value>> {
+    let mut values = values.into_iter();
+    primary_key.iter()
+        .map(|pk| if *pk { values.next().flatten() } else { None })
+        .collect()
+}
+
+struct ChangesetReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl ChangesetReader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], String> {
+        // Lengths come from the file, so a corrupt one must not overflow the position
+        let end = self.pos.checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Invalid changeset: unexpected end of data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+    
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+    
+    // SQLite varint: big-endian groups of 7 bits, the 9th byte contributes all 8 bits
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value: u64 = 0;
+        for _ in 0..8 {
+            let byte = self.byte()?;
+            value = (value << 7) | u64::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Ok((value << 8) | u64::from(self.byte()?))
+    }
+    
+    fn nul_terminated(&mut self) -> Result<String, String> {
+        let rest = &self.data[self.pos..];
+        let len = match rest.iter().position(|b| *b == 0) {
+            Some(len) => len,
+            None => return Err("Invalid changeset: unterminated table name".to_string()),
+        };
+        let name = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(name)
+    }
+    
+    // Values are None when undefined, i.e. not recorded for this change
+    fn record(&mut self, column_count: usize) -> Result<Vec<Option<Value>>, String> {
+        let mut values = Vec::with_capacity(column_count);
+        
+        for _ in 0..column_count {
+            let value = match self.byte()? {
+                0x00 => None,
+                0x01 => {
+                    let bytes: [u8; 8] = self.bytes(8)?.try_into().unwrap();
+                    Some(Value::Number(i64::from_be_bytes(bytes).into()))
+                },
+                0x02 => {
+                    let bytes: [u8; 8] = self.bytes(8)?.try_into().unwrap();
+                    let f = f64::from_be_bytes(bytes);
+                    Some(serde_json::Number::from_f64(f).map(Value::Number).unwrap_or(Value::String(f.to_string())))
+                },
+                0x03 => {
+                    let len = self.varint()? as usize;
+                    Some(Value::String(String::from_utf8_lossy(self.bytes(len)?).into_owned()))
+                },
+                0x04 => {
+                    let len = self.varint()? as usize;
+                    self.bytes(len)?;
+                    Some(Value::String(format!("BLOB({} bytes)", len)))
+                },
+                0x05 => Some(Value::Null),
+                other => return Err(format!("Invalid changeset: unknown value type 0x{:02x}", other)),
+            };
+            values.push(value);
+        }
+        
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    // Records the changes made by statements run after the session is attached
+    fn record(patchset: bool, statements: &str) -> Vec<u8> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, score REAL, avatar BLOB);
+            INSERT INTO users VALUES (1, 'ada', 1.5, NULL), (2, 'bob', 2.0, x'0102');
+        ").unwrap();
+        
+        let mut session = Session::new(&conn).unwrap();
+        session.attach(None).unwrap();
+        conn.execute_batch(statements).unwrap();
+        
+        let mut data = Vec::new();
+        if patchset {
+            session.patchset_strm(&mut data).unwrap();
+        } else {
+            session.changeset_strm(&mut data).unwrap();
+        }
+        data
+    }
+    
+    #[test]
+    fn parses_changeset_rows() {
+        let data = record(false, "
+            INSERT INTO users VALUES (3, 'cy', NULL, x'ff');
+            UPDATE users SET name = 'ada l.' WHERE id = 1;
+            DELETE FROM users WHERE id = 2;
+        ");
+        let mut entries = parse_changeset(&data).unwrap();
+        entries.sort_by_key(|entry| entry.operation.clone());
+        assert_eq!(entries.len(), 3);
+        
+        let delete = &entries[0];
+        assert_eq!(delete.operation, "DELETE");
+        assert_eq!(delete.table, "users");
+        assert_eq!(delete.primary_key, vec![0]);
+        assert_eq!(delete.old_values, vec![json!(2), json!("bob"), json!(2.0), json!("BLOB(2 bytes)")]);
+        assert!(delete.new_values.is_empty());
+        
+        let insert = &entries[1];
+        assert_eq!(insert.operation, "INSERT");
+        assert!(insert.old_values.is_empty());
+        assert_eq!(insert.new_values, vec![json!(3), json!("cy"), Value::Null, json!("BLOB(1 bytes)")]);
+        
+        // Only the primary key and the changed columns are recorded for updates
+        let update = &entries[2];
+        assert_eq!(update.operation, "UPDATE");
+        assert_eq!(update.old_values, vec![json!(1), json!("ada"), Value::Null, Value::Null]);
+        assert_eq!(update.new_values, vec![Value::Null, json!("ada l."), Value::Null, Value::Null]);
+        assert_eq!(update.changed_columns, vec![1]);
+    }
+    
+    #[test]
+    fn parses_patchset_rows() {
+        let data = record(true, "
+            UPDATE users SET score = 9.5 WHERE id = 1;
+            DELETE FROM users WHERE id = 2;
+        ");
+        let mut entries = parse_changeset(&data).unwrap();
+        entries.sort_by_key(|entry| entry.operation.clone());
+        assert_eq!(entries.len(), 2);
+        
+        // Patchset deletes keep only the primary key
+        assert_eq!(entries[0].operation, "DELETE");
+        assert_eq!(entries[0].old_values, vec![json!(2), Value::Null, Value::Null, Value::Null]);
+        
+        assert_eq!(entries[1].operation, "UPDATE");
+        assert_eq!(entries[1].old_values, vec![json!(1), Value::Null, Value::Null, Value::Null]);
+        assert_eq!(entries[1].new_values, vec![json!(1), Value::Null, json!(9.5), Value::Null]);
+        assert_eq!(entries[1].changed_columns, vec![2]);
+    }
+    
+    #[test]
+    fn rejects_truncated_and_garbage_data() {
+        let data = record(false, "INSERT INTO users VALUES (3, 'cy', NULL, NULL);");
+        // A table header on its own is a valid, empty changeset; cutting into the row is not
+        let name_start = 2 + data[1] as usize;
+        let header_len = name_start + data[name_start..].iter().position(|b| *b == 0).unwrap() + 1;
+        assert!(parse_changeset(&data[..header_len]).unwrap().is_empty());
+        for len in header_len + 1..data.len() {
+            assert!(parse_changeset(&data[..len]).is_err(), "truncated at {} bytes", len);
+        }
+        
+        assert!(parse_changeset(b"garbage").is_err());
+        // A text value claiming a length near usize::MAX
+        let mut huge = b"T\x01\x01t\x00\x12\x00\x03".to_vec();
+        huge.extend([0xff; 9]);
+        assert!(parse_changeset(&huge).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 mod database;
 
 use database::{sqlite, mongodb, postgres};
-use database::models::{ConnectionConfig, QueryResult, ConnectionResponse};
+use database::models::{ConnectionConfig, QueryResult, ConnectionResponse, PragmaInfo, SqlitePragmas, SqliteOptions, DatabaseStructure, TableDescription, DatabaseObject, QueryPlan, CopyOptions, CopyResult, SessionInfo, LockNode, RoleInfo, PrivilegeInfo, EffectivePrivilege, GrantRequest, TableStats, DatabaseSize, ExtensionInfo, FunctionInfo, PipelineStagePreview, CollectionSchema, MongoIndexInfo};
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -10,8 +10,8 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn connect_sqlite(path: String) -> Result<ConnectionResponse, String> {
-    match sqlite::connect_sqlite(&path) {
+async fn connect_sqlite(path: String, options: Option<SqliteOptions>) -> Result<ConnectionResponse, String> {
+    match sqlite::connect_sqlite(&path, options.as_ref()) {
         Ok(connection_id) => Ok(ConnectionResponse { 
             connection_id, 
             message: "SQLite connection established".to_string(),
@@ -42,8 +42,8 @@ async fn connect_mongodb(config: ConnectionConfig) -> Result<ConnectionResponse,
 }
 
 #[tauri::command]
-async fn connect_postgres(config: ConnectionConfig) -> Result<ConnectionResponse, String> {
-    match postgres::connect_postgres(&config).await {
+async fn connect_postgres(app: tauri::AppHandle, config: ConnectionConfig) -> Result<ConnectionResponse, String> {
+    match postgres::connect_postgres(app, &config).await {
         Ok(connection_id) => Ok(ConnectionResponse { 
             connection_id, 
             message: "PostgreSQL connection established".to_string(),
@@ -57,21 +57,102 @@ async fn connect_postgres(config: ConnectionConfig) -> Result<ConnectionResponse
     }
 }
 
+// Closing a connection also stops anything still watching or monitoring it
 #[tauri::command]
-async fn execute_query(connection_id: String, query: String, db_type: String) -> Result<QueryResult, String> {
+async fn close_connection(connection_id: String, db_type: String) -> Result<(), String> {
+    match db_type.as_str() {
+        "sqlite" => sqlite::close_connection(&connection_id),
+        "mongodb" => mongodb::close_connection(&connection_id),
+        "postgres" => postgres::close_connection(&connection_id),
+        _ => Err(format!("Closing connections is not supported for {}", db_type)),
+    }
+}
+
+#[tauri::command]
+async fn execute_query(connection_id: String, query: String, db_type: String, extended_json: Option<String>) -> Result<QueryResult, String> {
     match db_type.as_str() {
         "sqlite" => sqlite::execute_query(connection_id, query),
-        "mongodb" => mongodb::execute_query(connection_id, query).await,
+        "mongodb" => mongodb::execute_query(connection_id, query, extended_json.as_deref()).await,
         "postgres" => postgres::execute_query(connection_id, query).await,
         _ => Err(format!("Unsupported database type: {}", db_type)),
     }
 }
 
+#[tauri::command]
+async fn explain_query(connection_id: String, query: String, db_type: String, analyze: Option<bool>) -> Result<QueryPlan, String> {
+    match db_type.as_str() {
+        "sqlite" => sqlite::explain_query(&connection_id, &query),
+        "postgres" => postgres::explain::explain_query(&connection_id, &query, analyze.unwrap_or(false)).await,
+        _ => Err(format!("Query plans are not supported for {}", db_type)),
+    }
+}
+
 #[tauri::command]
 async fn get_tables(connection_id: String) -> Result<Vec<String>, String> {
     sqlite::get_database_structure(&connection_id)
 }
 
+#[tauri::command]
+async fn get_pragmas(connection_id: String) -> Result<SqlitePragmas, String> {
+    sqlite::get_pragmas(&connection_id)
+}
+
+#[tauri::command]
+async fn set_pragma(connection_id: String, name: String, value: serde_json::Value) -> Result<PragmaInfo, String> {
+    sqlite::set_pragma(&connection_id, &name, value)
+}
+
+#[tauri::command]
+async fn rekey_sqlite(connection_id: String, new_key: String, raw_key: Option<bool>) -> Result<(), String> {
+    sqlite::rekey(&connection_id, &new_key, raw_key.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn watch_sqlite(app: tauri::AppHandle, connection_id: String) -> Result<(), String> {
+    sqlite::watch_file(app, &connection_id)
+}
+
+#[tauri::command]
+async fn unwatch_sqlite(connection_id: String) -> Result<(), String> {
+    sqlite::unwatch_file(&connection_id)
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn start_sqlite_session(connection_id: String, tables: Option<Vec<String>>) -> Result<(), String> {
+    sqlite::session::start_session(&connection_id, tables)
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn stop_sqlite_session(connection_id: String) -> Result<(), String> {
+    sqlite::session::stop_session(&connection_id)
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn list_sqlite_changes(connection_id: String, path: Option<String>) -> Result<Vec<database::models::ChangesetEntry>, String> {
+    sqlite::session::list_changes(&connection_id, path.as_deref())
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn export_sqlite_changeset(connection_id: String, path: String, patchset: Option<bool>) -> Result<u64, String> {
+    sqlite::session::export_session(&connection_id, &path, patchset.unwrap_or(false))
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn apply_sqlite_changeset(connection_id: String, path: String, invert: Option<bool>, on_conflict: Option<String>) -> Result<u64, String> {
+    sqlite::session::apply_changeset(&connection_id, &path, invert.unwrap_or(false), on_conflict.as_deref())
+}
+
+#[cfg(feature = "sqlite-session")]
+#[tauri::command]
+async fn invert_sqlite_changeset(input_path: String, output_path: String) -> Result<(), String> {
+    sqlite::session::invert_changeset(&input_path, &output_path)
+}
+
 #[tauri::command]
 async fn list_databases(connection_id: String) -> Result<Vec<String>, String> {
     // This function handles all database types
@@ -102,6 +183,193 @@ async fn list_tables(connection_id: String, schema: Option<String>) -> Result<Ve
     postgres::list_tables(&connection_id, schema.as_deref()).await
 }
 
+#[tauri::command]
+async fn list_schemas(connection_id: String, include_system: Option<bool>) -> Result<Vec<String>, String> {
+    postgres::list_schemas(&connection_id, include_system.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn describe_table(connection_id: String, schema: Option<String>, table: String) -> Result<TableDescription, String> {
+    postgres::describe_table(&connection_id, schema.as_deref(), &table).await
+}
+
+#[tauri::command]
+async fn get_postgres_structure(connection_id: String, include_system: Option<bool>) -> Result<DatabaseStructure, String> {
+    postgres::get_database_structure(&connection_id, include_system.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn get_ddl(connection_id: String, object: DatabaseObject) -> Result<String, String> {
+    postgres::ddl::get_ddl(&connection_id, &object).await
+}
+
+#[tauri::command]
+async fn listen(connection_id: String, channel: String) -> Result<(), String> {
+    postgres::listen(&connection_id, &channel).await
+}
+
+#[tauri::command]
+async fn unlisten(connection_id: String, channel: Option<String>) -> Result<(), String> {
+    postgres::unlisten(&connection_id, channel.as_deref()).await
+}
+
+#[tauri::command]
+async fn notify(connection_id: String, channel: String, payload: Option<String>) -> Result<(), String> {
+    postgres::notify(&connection_id, &channel, payload.as_deref()).await
+}
+
+#[tauri::command]
+async fn list_channels(connection_id: String) -> Result<Vec<String>, String> {
+    postgres::list_channels(&connection_id).await
+}
+
+#[tauri::command]
+async fn copy_in(app: tauri::AppHandle, connection_id: String, schema: Option<String>, table: String, path: String, options: Option<CopyOptions>) -> Result<CopyResult, String> {
+    postgres::copy::copy_in(app, &connection_id, schema.as_deref(), &table, &path, &options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn copy_out(app: tauri::AppHandle, connection_id: String, query: String, path: String, options: Option<CopyOptions>) -> Result<CopyResult, String> {
+    postgres::copy::copy_out(app, &connection_id, &query, &path, &options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn get_sessions(connection_id: String, include_idle: Option<bool>) -> Result<Vec<SessionInfo>, String> {
+    postgres::activity::get_sessions(&connection_id, include_idle.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn get_lock_tree(connection_id: String) -> Result<Vec<LockNode>, String> {
+    postgres::activity::get_lock_tree(&connection_id).await
+}
+
+#[tauri::command]
+async fn cancel_backend(connection_id: String, pid: i32) -> Result<bool, String> {
+    postgres::activity::cancel_backend(&connection_id, pid).await
+}
+
+#[tauri::command]
+async fn terminate_backend(connection_id: String, pid: i32) -> Result<bool, String> {
+    postgres::activity::terminate_backend(&connection_id, pid).await
+}
+
+#[tauri::command]
+async fn start_activity_monitor(app: tauri::AppHandle, connection_id: String, interval_ms: Option<u64>, include_idle: Option<bool>) -> Result<(), String> {
+    postgres::activity::start_monitor(app, &connection_id, interval_ms.unwrap_or(2000), include_idle.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn stop_activity_monitor(connection_id: String) -> Result<(), String> {
+    postgres::activity::stop_monitor(&connection_id)
+}
+
+#[tauri::command]
+async fn list_roles(connection_id: String) -> Result<Vec<RoleInfo>, String> {
+    postgres::roles::list_roles(&connection_id).await
+}
+
+#[tauri::command]
+async fn get_privileges(connection_id: String, object: DatabaseObject) -> Result<Vec<PrivilegeInfo>, String> {
+    postgres::roles::get_privileges(&connection_id, &object).await
+}
+
+#[tauri::command]
+async fn get_effective_privileges(connection_id: String, object: DatabaseObject, role: Option<String>) -> Result<Vec<EffectivePrivilege>, String> {
+    postgres::roles::get_effective_privileges(&connection_id, &object, role.as_deref()).await
+}
+
+#[tauri::command]
+async fn generate_grant(connection_id: String, request: GrantRequest) -> Result<String, String> {
+    postgres::roles::generate_grant(&connection_id, &request).await
+}
+
+#[tauri::command]
+async fn apply_grant(connection_id: String, request: GrantRequest) -> Result<String, String> {
+    postgres::roles::apply_grant(&connection_id, &request).await
+}
+
+#[tauri::command]
+async fn table_stats(connection_id: String, schema: Option<String>, table: Option<String>) -> Result<Vec<TableStats>, String> {
+    postgres::stats::table_stats(&connection_id, schema.as_deref(), table.as_deref()).await
+}
+
+// Separate from list_databases, which returns bare names for every backend to the explorer tree;
+// pg_database_size walks each database's files, too slow to run on every tree refresh
+#[tauri::command]
+async fn database_sizes(connection_id: String) -> Result<Vec<DatabaseSize>, String> {
+    postgres::stats::database_sizes(&connection_id).await
+}
+
+#[tauri::command]
+async fn list_extensions(connection_id: String) -> Result<Vec<ExtensionInfo>, String> {
+    postgres::extensions::list_extensions(&connection_id).await
+}
+
+#[tauri::command]
+async fn create_extension(connection_id: String, name: String, schema: Option<String>, version: Option<String>, cascade: Option<bool>) -> Result<String, String> {
+    postgres::extensions::create_extension(&connection_id, &name, schema.as_deref(), version.as_deref(), cascade.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn update_extension(connection_id: String, name: String, version: Option<String>) -> Result<String, String> {
+    postgres::extensions::update_extension(&connection_id, &name, version.as_deref()).await
+}
+
+#[tauri::command]
+async fn list_functions(connection_id: String, schema: Option<String>) -> Result<Vec<FunctionInfo>, String> {
+    postgres::functions::list_functions(&connection_id, schema.as_deref()).await
+}
+
+#[tauri::command]
+async fn build_function_call(connection_id: String, schema: Option<String>, name: String, args: Vec<serde_json::Value>) -> Result<String, String> {
+    postgres::functions::build_function_call(&connection_id, schema.as_deref(), &name, &args).await
+}
+
+#[tauri::command]
+async fn execute_function(connection_id: String, schema: Option<String>, name: String, args: Vec<serde_json::Value>) -> Result<QueryResult, String> {
+    postgres::functions::execute_function(&connection_id, schema.as_deref(), &name, &args).await
+}
+
+#[tauri::command]
+async fn export_geojson(connection_id: String, query: String, path: String) -> Result<usize, String> {
+    postgres::geo::export_geojson(&connection_id, &query, &path).await
+}
+
+#[tauri::command]
+async fn preview_aggregate(connection_id: String, query: String, sample_size: Option<i64>, extended_json: Option<String>) -> Result<Vec<PipelineStagePreview>, String> {
+    mongodb::preview_aggregate(connection_id, query, sample_size.unwrap_or(20), extended_json.as_deref()).await
+}
+
+#[tauri::command]
+async fn infer_schema(connection_id: String, db: String, collection: String, sample_size: Option<i64>) -> Result<CollectionSchema, String> {
+    mongodb::schema::infer_schema(&connection_id, &db, &collection, sample_size.unwrap_or(1000)).await
+}
+
+#[tauri::command]
+async fn list_indexes(connection_id: String, db: String, collection: String) -> Result<Vec<MongoIndexInfo>, String> {
+    mongodb::indexes::list_indexes(&connection_id, &db, &collection).await
+}
+
+#[tauri::command]
+async fn create_index(connection_id: String, db: String, collection: String, keys: serde_json::Value, options: Option<serde_json::Value>) -> Result<String, String> {
+    mongodb::indexes::create_index(&connection_id, &db, &collection, &keys, options.as_ref()).await
+}
+
+#[tauri::command]
+async fn drop_index(connection_id: String, db: String, collection: String, name: String) -> Result<(), String> {
+    mongodb::indexes::drop_index(&connection_id, &db, &collection, &name).await
+}
+
+#[tauri::command]
+async fn watch_mongodb(app: tauri::AppHandle, connection_id: String, db: String, collection: Option<String>, pipeline: Option<serde_json::Value>, resume_after: Option<serde_json::Value>) -> Result<String, String> {
+    mongodb::watch::watch(app, &connection_id, &db, collection.as_deref(), pipeline.as_ref(), resume_after.as_ref()).await
+}
+
+#[tauri::command]
+async fn unwatch_mongodb(watch_id: String) -> Result<(), String> {
+    mongodb::watch::stop_watch(&watch_id)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -113,11 +381,67 @@ pub fn run() {
             connect_sqlite,
             connect_mongodb,
             connect_postgres,
+            close_connection,
             execute_query,
+            explain_query,
             get_tables,
+            get_pragmas,
+            set_pragma,
+            rekey_sqlite,
+            watch_sqlite,
+            unwatch_sqlite,
+            #[cfg(feature = "sqlite-session")]
+            start_sqlite_session,
+            #[cfg(feature = "sqlite-session")]
+            stop_sqlite_session,
+            #[cfg(feature = "sqlite-session")]
+            list_sqlite_changes,
+            #[cfg(feature = "sqlite-session")]
+            export_sqlite_changeset,
+            #[cfg(feature = "sqlite-session")]
+            apply_sqlite_changeset,
+            #[cfg(feature = "sqlite-session")]
+            invert_sqlite_changeset,
             list_databases,
             list_collections,
-            list_tables
+            list_tables,
+            list_schemas,
+            get_postgres_structure,
+            describe_table,
+            get_ddl,
+            listen,
+            unlisten,
+            notify,
+            list_channels,
+            copy_in,
+            copy_out,
+            get_sessions,
+            get_lock_tree,
+            cancel_backend,
+            terminate_backend,
+            start_activity_monitor,
+            stop_activity_monitor,
+            list_roles,
+            get_privileges,
+            get_effective_privileges,
+            generate_grant,
+            apply_grant,
+            table_stats,
+            database_sizes,
+            list_extensions,
+            create_extension,
+            update_extension,
+            list_functions,
+            build_function_call,
+            execute_function,
+            export_geojson,
+            preview_aggregate,
+            infer_schema,
+            list_indexes,
+            create_index,
+            drop_index,
+            watch_mongodb,
+            unwatch_mongodb
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");